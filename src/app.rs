@@ -1,25 +1,36 @@
-use crate::audio::{encode_m4a, CpalRecorder, RecordingHandle};
+use crate::audio::{CpalRecorder, RecordedAudio, RecordingHandle};
 use crate::beep;
 use crate::cli::{Cli, Commands, RunArgs, TranscribeArgs};
 use crate::clipboard::Clipboard;
 use crate::config::{AutoTranscribeConfig, Config, ConfigStore, WatchPair};
+use crate::encode::{encode_recording, encode_wav, RecordingFormat};
+use crate::hotkeys::{self, HotkeyAction, RecordKeyResponse, RecordProfile};
+use crate::journal;
+use crate::live::LiveSession;
 use crate::logging;
 use crate::model;
-use crate::queue::{AutoJob, Job, JobKind, JobQueue, HotkeyJob};
+use crate::output::{self, OutputFormat};
+use crate::queue::{AutoJob, Job, JobKind, JobQueue, HotkeyJob, RefineJob};
+use crate::remote::{RemoteConfig, RemoteTranscriber};
 use crate::storage;
-use crate::transcriber::WhisperTranscriber;
+use crate::transcriber::{
+    CancelToken, Cancelled, LatencyMode, Progress, TranscribeOptions, Transcriber,
+    WhisperTranscriber,
+};
+use crate::transcript::Transcript;
 use crate::tray::{TrayAction, TrayController, TrayState};
+use crate::vad::VadConfig;
 use anyhow::{Context, Result};
 use clap::Parser;
 use crossbeam_channel::{unbounded, Receiver, Sender};
-use global_hotkey::hotkey::{Code, HotKey, Modifiers};
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 use indicatif::{ProgressBar, ProgressStyle};
 use notify::{Event as NotifyEvent, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 use tao::event::{Event, StartCause};
@@ -34,14 +45,24 @@ enum WorkerEvent {
     ModelReady(PathBuf),
     ModelProgress(u8),
     ModelError(String),
+    DraftModelReady(PathBuf),
+    DraftModelError(String),
     HotkeyRecordingReady(HotkeyJob),
     HotkeyRecordingError(String),
     AutoFileDetected(AutoJobSpec),
     TranscriptionProgress(u8),
     HotkeyTranscriptionDone { text: String },
     HotkeyTranscriptionError(String),
+    HotkeyTranscriptionCancelled,
+    LivePartial { text: String },
+    LiveTranscriptionDone { text: String },
+    RecordingRecovered(RefineJob),
+    RefineDone { text: String, draft: Option<String> },
+    RefineError(String),
+    RefineCancelled,
     AutoTranscriptionDone { input_path: PathBuf },
     AutoTranscriptionError { input_path: PathBuf, error: String },
+    AutoTranscriptionCancelled { input_path: PathBuf },
     Error(String),
 }
 
@@ -50,6 +71,10 @@ struct AutoJobSpec {
     input_path: PathBuf,
     output_dir: PathBuf,
     processed_dir: PathBuf,
+    language: Option<String>,
+    translate: Option<bool>,
+    vad: Option<VadConfig>,
+    split_channels: bool,
 }
 
 pub fn run() -> Result<()> {
@@ -64,6 +89,17 @@ pub fn run() -> Result<()> {
 
 fn run_transcribe(args: TranscribeArgs) -> Result<()> {
     tracing::info!(input = %args.input.display(), "transcribe file");
+    let formats = if args.formats.is_empty() {
+        vec![OutputFormat::Md]
+    } else {
+        let mut formats = args.formats.clone();
+        let mut seen = HashSet::new();
+        formats.retain(|format| seen.insert(*format));
+        formats
+    };
+    if args.output.is_some() && formats.len() > 1 {
+        anyhow::bail!("--output takes a single --format; use --output-dir for several");
+    }
     let store = ConfigStore::new()?;
     let config = store.load()?;
     let model = args
@@ -72,9 +108,28 @@ fn run_transcribe(args: TranscribeArgs) -> Result<()> {
         .unwrap_or(config.model.as_str())
         .to_string();
     let vocabulary_prompt = vocabulary_prompt(&config.vocabulary);
-    let models_dir = default_models_dir()?;
-    let model_path = model::ensure_model(&models_dir, &model)?;
-    let transcriber = WhisperTranscriber::new(model_path)?;
+    let translate = args.translate || config.translate;
+    let remote = match args.remote_url.clone() {
+        Some(base_url) => Some(RemoteConfig {
+            base_url,
+            ..config.remote.clone().unwrap_or_default()
+        }),
+        None => config.remote.clone(),
+    };
+    let transcriber: Box<dyn Transcriber> = match remote {
+        Some(remote) => {
+            tracing::info!(base_url = %remote.base_url, model = %remote.model, "using remote backend");
+            Box::new(RemoteTranscriber::new(remote)?)
+        }
+        None => {
+            if translate && !model::supports_translation(&model) {
+                anyhow::bail!("model '{model}' is English-only and cannot translate");
+            }
+            let models_dir = default_models_dir()?;
+            let model_path = model::ensure_model(&models_dir, &model)?;
+            Box::new(WhisperTranscriber::new(model_path)?)
+        }
+    };
     let pb = ProgressBar::new(100);
     let style = ProgressStyle::with_template("{spinner} {bar:40} {pos}% {msg}")
         .unwrap_or_else(|_| ProgressStyle::default_bar())
@@ -84,9 +139,39 @@ fn run_transcribe(args: TranscribeArgs) -> Result<()> {
     pb.set_message("transcribing");
     pb.enable_steady_tick(Duration::from_millis(120));
     let pb_ref = pb.clone();
-    let text = match transcriber.transcribe_file_with_progress_and_prompt(
+    let options = TranscribeOptions {
+        prompt: vocabulary_prompt,
+        language: args.language.clone().or_else(|| Some(config.language.clone())),
+        translate,
+        languages: if args.languages.is_empty() {
+            config.languages.clone()
+        } else {
+            args.languages.clone()
+        },
+        preprocess: config.preprocess.clone(),
+        vad: VadConfig {
+            detector: args.vad.unwrap_or(config.vad.detector),
+            ..config.vad.clone()
+        },
+        split_channels: args.split_channels,
+        filter: config.hallucination_filter.clone(),
+        device: args.device.unwrap_or(config.device),
+        threads: args.threads.or(config.threads),
+        retry_on_cpu: args.retry_on_cpu || config.retry_on_cpu,
+        latency: args.latency.unwrap_or(config.latency),
+        cancel: CancelToken::new(),
+    };
+    let cancel = options.cancel.clone();
+    ctrlc::set_handler(move || {
+        if cancel.is_cancelled() {
+            std::process::exit(130);
+        }
+        cancel.cancel();
+    })
+    .context("install Ctrl-C handler")?;
+    let transcript = match transcriber.transcribe(
         &args.input,
-        Some(move |pct| {
+        Some(Box::new(move |pct| {
             let pct = if pct < 0 {
                 0
             } else if pct > 100 {
@@ -95,22 +180,50 @@ fn run_transcribe(args: TranscribeArgs) -> Result<()> {
                 pct
             };
             pb_ref.set_position(pct as u64);
-        }),
-        vocabulary_prompt.as_deref(),
-        args.language.as_deref(),
+        })),
+        &options,
     ) {
-        Ok(text) => text,
+        Ok(transcript) => transcript,
+        Err(err) if err.is::<Cancelled>() => {
+            pb.finish_and_clear();
+            eprintln!("Transcription cancelled");
+            std::process::exit(130);
+        }
         Err(err) => {
             pb.finish_and_clear();
             return Err(err);
         }
     };
     pb.finish_and_clear();
-    let output = storage::transcript_path_for_input(&args.input)?;
-    fs::write(&output, &text)
-        .with_context(|| format!("write transcript {}", output.display()))?;
-    println!("{text}");
-    tracing::info!(output = %output.display(), "transcription complete");
+    if let Some(dir) = args.output_dir.as_deref() {
+        storage::ensure_dir(dir)?;
+    }
+    for format in formats {
+        let output = match args.output.clone() {
+            Some(path) => path,
+            None => storage::output_path_for_input(
+                &args.input,
+                args.output_dir.as_deref(),
+                format.extension(),
+            )?,
+        };
+        let contents = output::render(&transcript, format)?;
+        fs::write(&output, contents)
+            .with_context(|| format!("write transcript {}", output.display()))?;
+        tracing::info!(output = %output.display(), format = format.extension(), "wrote transcript");
+    }
+    if let Some(device) = transcript.device {
+        eprintln!("Device: {}", device.label());
+    }
+    if let Some(language) = &transcript.language {
+        eprintln!(
+            "Detected language: {} ({:.0}%)",
+            language.code,
+            language.probability * 100.0
+        );
+    }
+    println!("{}", transcript.text());
+    tracing::info!("transcription complete");
     Ok(())
 }
 
@@ -172,18 +285,37 @@ fn run_daemon(args: RunArgs) -> Result<()> {
         config.model = model;
     }
     config.recordings_dir = args.recordings_dir.clone();
+    check_translation_models(&config)?;
     store.save(&config)?;
     let vocabulary_prompt = vocabulary_prompt(&config.vocabulary);
 
     storage::ensure_dir(&config.recordings_dir)?;
+    let unfinished_journals = journal::unfinished_journals(&config.recordings_dir)?;
+    if !unfinished_journals.is_empty() {
+        tracing::warn!(count = unfinished_journals.len(), "found unfinished recordings");
+    }
     let devices = CpalRecorder::list_devices()?;
     let default_mic = CpalRecorder::default_device_name()?;
-    let tray = TrayController::new(
+    let mut tray = TrayController::new(
         &devices,
         config.selected_mic.as_deref(),
         default_mic.as_deref(),
+        config.hotkey_mode,
+        &config.hotkeys,
     )?;
-    tray.set_state(TrayState::Downloading { progress: None })?;
+    tray.set_recoverable(unfinished_journals.len())?;
+    let remote = match config.remote.clone() {
+        Some(remote) => {
+            tracing::info!(base_url = %remote.base_url, model = %remote.model, "using remote backend");
+            Some(Arc::new(RemoteTranscriber::new(remote)?) as Arc<dyn Transcriber>)
+        }
+        None => None,
+    };
+    tray.set_state(if remote.is_some() {
+        TrayState::Idle
+    } else {
+        TrayState::Downloading { progress: None }
+    })?;
     let beep = match beep::BeepPlayer::new() {
         Ok(player) => Some(player),
         Err(err) => {
@@ -196,27 +328,46 @@ fn run_daemon(args: RunArgs) -> Result<()> {
     if let Some(auto_cfg) = config.auto_transcribe.clone() {
         spawn_auto_transcribe_watchers(auto_cfg, worker_tx.clone())?;
     }
-    let models_dir = default_models_dir()?;
-    spawn_model_download(models_dir.clone(), config.model.clone(), worker_tx.clone());
+    if remote.is_none() {
+        let models_dir = default_models_dir()?;
+        spawn_model_download(models_dir.clone(), config.model.clone(), worker_tx.clone());
+    }
+    if let Some(two_pass) = &config.two_pass {
+        spawn_draft_model_download(
+            default_models_dir()?,
+            two_pass.draft_model.clone(),
+            worker_tx.clone(),
+        );
+    }
 
         let app = App {
             config,
             store,
             tray,
             beep,
-            downloading_model: true,
+            downloading_model: remote.is_none(),
             model_download_progress: None,
-            model_path: None,
+            transcriber: remote,
+            loaded_model: None,
+            draft_transcriber: None,
+            draft_job: None,
             recordings_dir: args.recordings_dir,
+            unfinished_journals,
             worker_rx,
             worker_tx,
             recording: None,
+            recording_started: None,
+            recording_profile: RecordProfile::default(),
+            live: None,
             queue: JobQueue::new(),
             transcription_progress: None,
             auto_inflight: HashSet::new(),
             vocabulary_prompt,
             last_theme_check: Instant::now(),
             hotkey_pending: false,
+            hotkey_cancel: None,
+            background_cancel: None,
+            last_transcript: None,
         };
 
     app.event_loop()
@@ -229,17 +380,36 @@ struct App {
     beep: Option<beep::BeepPlayer>,
     downloading_model: bool,
     model_download_progress: Option<u8>,
-    model_path: Option<PathBuf>,
+    transcriber: Option<Arc<dyn Transcriber>>,
+    /// Model file behind `transcriber` when it runs locally.
+    loaded_model: Option<PathBuf>,
+    /// Small model for the first of two passes over hotkey recordings.
+    draft_transcriber: Option<Arc<dyn Transcriber>>,
+    /// Hotkey job running on the draft model; refined once its draft is delivered.
+    draft_job: Option<HotkeyJob>,
     recordings_dir: PathBuf,
+    /// Journals of recordings interrupted by a crash, offered for recovery in the tray.
+    unfinished_journals: Vec<PathBuf>,
     worker_rx: Receiver<WorkerEvent>,
     worker_tx: Sender<WorkerEvent>,
     recording: Option<RecordingHandle>,
+    /// When the current recording started, for the push-to-talk minimum.
+    recording_started: Option<Instant>,
+    /// Overrides from the hotkey that started the current recording.
+    recording_profile: RecordProfile,
+    live: Option<LiveSession>,
     queue: JobQueue,
     transcription_progress: Option<u8>,
     auto_inflight: HashSet<PathBuf>,
     vocabulary_prompt: Option<String>,
     last_theme_check: Instant,
     hotkey_pending: bool,
+    /// Cancels the current hotkey recording's transcription, live or queued.
+    hotkey_cancel: Option<CancelToken>,
+    /// Cancels the running refine or auto job.
+    background_cancel: Option<CancelToken>,
+    /// Latest hotkey transcript, for the `recopy_last` hotkey.
+    last_transcript: Option<String>,
 }
 
 impl App {
@@ -250,10 +420,14 @@ impl App {
             event_loop.set_activation_policy(ActivationPolicy::Accessory);
         }
         let hotkey_manager = GlobalHotKeyManager::new().context("init hotkey manager")?;
-        let hotkey = HotKey::new(Some(Modifiers::ALT), Code::Space);
-        hotkey_manager
-            .register(hotkey)
-            .context("register Command+Space")?;
+        let mut hotkey_actions = HashMap::new();
+        let bindings = hotkeys::parse_bindings(&self.config.hotkeys)?;
+        for ((hotkey, action), binding) in bindings.into_iter().zip(&self.config.hotkeys) {
+            hotkey_manager
+                .register(hotkey)
+                .with_context(|| format!("register {}", binding.label()))?;
+            hotkey_actions.insert(hotkey.id(), action);
+        }
         let hotkey_rx = GlobalHotKeyEvent::receiver();
         let menu_rx = MenuEvent::receiver();
         let tray_rx = TrayIconEvent::receiver();
@@ -266,10 +440,10 @@ impl App {
                 }
                 Event::MainEventsCleared => {
                     while let Ok(ev) = hotkey_rx.try_recv() {
-                        if ev.id == hotkey.id() && ev.state == HotKeyState::Pressed {
-                            if let Err(err) = self.handle_hotkey() {
-                                tracing::error!(error = %err, "hotkey handler failed");
-                            }
+                        if let Some(action) = hotkey_actions.get(&ev.id)
+                            && let Err(err) = self.handle_hotkey(action, ev.state)
+                        {
+                            tracing::error!(error = %err, "hotkey handler failed");
                         }
                     }
                     while let Ok(tray_event) = tray_rx.try_recv() {
@@ -328,7 +502,13 @@ impl App {
                 self.store.save(&self.config)?;
             }
             TrayAction::ToggleRecording => {
-                self.handle_hotkey()?;
+                self.toggle_recording(RecordProfile::default())?;
+            }
+            TrayAction::CancelTranscription => {
+                self.cancel_transcription();
+            }
+            TrayAction::RecoverRecordings => {
+                self.recover_recordings()?;
             }
         }
         Ok(())
@@ -350,7 +530,7 @@ impl App {
         match event {
             WorkerEvent::ModelReady(path) => {
                 tracing::info!(path = %path.display(), "model ready");
-                self.model_path = Some(path);
+                self.load_transcriber(path)?;
                 self.downloading_model = false;
                 self.model_download_progress = None;
                 self.update_tray_state()?;
@@ -368,16 +548,47 @@ impl App {
                 self.model_download_progress = None;
                 self.update_tray_state()?;
             }
+            WorkerEvent::DraftModelReady(path) => {
+                tracing::info!(path = %path.display(), "draft model ready");
+                let transcriber = Arc::new(WhisperTranscriber::new(path)?);
+                let preload = Arc::clone(&transcriber);
+                let device = self.config.device;
+                thread::spawn(move || {
+                    if let Err(err) = preload.preload(device) {
+                        tracing::warn!(error = %err, "draft model preload failed");
+                    }
+                });
+                self.draft_transcriber = Some(transcriber);
+            }
+            WorkerEvent::DraftModelError(err) => {
+                tracing::error!(error = %err, "draft model download failed; using one pass");
+            }
             WorkerEvent::HotkeyRecordingReady(job) => {
+                if self
+                    .hotkey_cancel
+                    .as_ref()
+                    .is_some_and(CancelToken::is_cancelled)
+                {
+                    return self.handle_worker(WorkerEvent::HotkeyTranscriptionCancelled);
+                }
                 if !self.queue.enqueue_hotkey(job) {
                     tracing::warn!("hotkey recording already queued");
                 }
+                // The background job resumes from the front of its queue once it has stopped.
+                if self.queue.preempt_background()
+                    && let Some(token) = &self.background_cancel
+                {
+                    tracing::info!("pausing background transcription for hotkey recording");
+                    token.cancel();
+                }
                 self.maybe_start_transcription()?;
             }
             WorkerEvent::HotkeyRecordingError(err) => {
                 tracing::error!(error = %err, "recording failed");
                 self.queue.cancel_hotkey_session();
                 self.hotkey_pending = false;
+                self.hotkey_cancel = None;
+                self.tray.set_live_preview(None)?;
                 self.update_tray_state()?;
             }
             WorkerEvent::AutoFileDetected(spec) => {
@@ -391,18 +602,83 @@ impl App {
             }
             WorkerEvent::HotkeyTranscriptionDone { text } => {
                 tracing::info!("transcription done");
-                println!("{text}");
-                let mut clipboard = Clipboard::new()?;
-                clipboard.set_text(&text)?;
+                if let Some(job) = self.draft_job.take() {
+                    self.queue.enqueue_refine(RefineJob {
+                        audio_path: job.audio_path,
+                        text_path: job.text_path,
+                        draft: Some(text.clone()),
+                        audio: Some(job.audio),
+                        profile: job.profile,
+                    });
+                }
+                self.deliver_hotkey_text(&text)?;
+            }
+            WorkerEvent::HotkeyTranscriptionError(err) => {
+                tracing::error!(error = %err, "transcription failed");
+                self.draft_job = None;
+                self.tray.set_live_preview(None)?;
                 self.transcription_progress = None;
                 self.queue.complete_active(JobKind::Hotkey);
+                self.hotkey_cancel = None;
                 self.update_tray_state()?;
                 self.maybe_start_transcription()?;
             }
-            WorkerEvent::HotkeyTranscriptionError(err) => {
-                tracing::error!(error = %err, "transcription failed");
+            WorkerEvent::HotkeyTranscriptionCancelled => {
+                tracing::info!("transcription cancelled");
+                self.draft_job = None;
+                self.tray.set_live_preview(None)?;
                 self.transcription_progress = None;
-                self.queue.complete_active(JobKind::Hotkey);
+                self.hotkey_pending = false;
+                self.hotkey_cancel = None;
+                self.queue.cancel_hotkey_session();
+                self.update_tray_state()?;
+                self.maybe_start_transcription()?;
+            }
+            WorkerEvent::LivePartial { text } => {
+                if self.recording.is_some() || self.hotkey_pending {
+                    self.tray.set_live_preview(Some(&text))?;
+                }
+            }
+            WorkerEvent::LiveTranscriptionDone { text } => {
+                tracing::info!("live transcription done");
+                self.hotkey_pending = false;
+                self.deliver_hotkey_text(&text)?;
+            }
+            WorkerEvent::RecordingRecovered(job) => {
+                tracing::info!(path = %job.audio_path.display(), "recovered recording");
+                self.queue.enqueue_refine(job);
+                self.maybe_start_transcription()?;
+            }
+            WorkerEvent::RefineDone { text, draft } => {
+                tracing::info!("refined transcript written");
+                let update_clipboard = self
+                    .config
+                    .two_pass
+                    .as_ref()
+                    .is_some_and(|two_pass| two_pass.update_clipboard);
+                if draft.is_some() && self.last_transcript == draft {
+                    self.last_transcript = Some(text.clone());
+                }
+                if let Some(draft) = draft
+                    && update_clipboard
+                    && let Err(err) = replace_clipboard_text(&draft, &text)
+                {
+                    tracing::warn!(error = %err, "failed to update clipboard with refined text");
+                }
+                self.finish_refine()?;
+            }
+            WorkerEvent::RefineError(err) => {
+                tracing::error!(error = %err, "refinement failed; keeping the draft");
+                self.finish_refine()?;
+            }
+            WorkerEvent::RefineCancelled => {
+                if self.queue.abort_active_background() {
+                    tracing::info!("refinement paused");
+                } else {
+                    tracing::info!("refinement cancelled; keeping the draft");
+                }
+                self.transcription_progress = None;
+                self.background_cancel = None;
                 self.update_tray_state()?;
                 self.maybe_start_transcription()?;
             }
@@ -410,6 +686,7 @@ impl App {
                 tracing::info!(path = %input_path.display(), "auto transcription done");
                 self.auto_inflight.remove(&input_path);
                 self.transcription_progress = None;
+                self.background_cancel = None;
                 self.queue.complete_active(JobKind::Auto);
                 self.update_tray_state()?;
                 self.maybe_start_transcription()?;
@@ -418,10 +695,24 @@ impl App {
                 tracing::error!(path = %input_path.display(), error = %error, "auto transcription failed");
                 self.auto_inflight.remove(&input_path);
                 self.transcription_progress = None;
+                self.background_cancel = None;
                 self.queue.complete_active(JobKind::Auto);
                 self.update_tray_state()?;
                 self.maybe_start_transcription()?;
             }
+            WorkerEvent::AutoTranscriptionCancelled { input_path } => {
+                // Either way the file stays in `auto_inflight`: a preempted job is
+                // queued again, and a cancelled one is not picked up until the next start.
+                if self.queue.abort_active_background() {
+                    tracing::info!(path = %input_path.display(), "auto transcription paused");
+                } else {
+                    tracing::info!(path = %input_path.display(), "auto transcription cancelled");
+                }
+                self.transcription_progress = None;
+                self.background_cancel = None;
+                self.update_tray_state()?;
+                self.maybe_start_transcription()?;
+            }
             WorkerEvent::Error(err) => {
                 tracing::error!(error = %err, "worker error");
                 self.update_tray_state()?;
@@ -430,7 +721,97 @@ impl App {
         Ok(())
     }
 
-    fn handle_hotkey(&mut self) -> Result<()> {
+    fn deliver_hotkey_text(&mut self, text: &str) -> Result<()> {
+        println!("{text}");
+        let mut clipboard = Clipboard::new()?;
+        clipboard.set_text(text)?;
+        self.last_transcript = Some(text.to_string());
+        self.tray.set_live_preview(None)?;
+        self.transcription_progress = None;
+        self.hotkey_cancel = None;
+        self.queue.complete_active(JobKind::Hotkey);
+        self.update_tray_state()?;
+        self.maybe_start_transcription()
+    }
+
+    fn finish_refine(&mut self) -> Result<()> {
+        self.transcription_progress = None;
+        self.background_cancel = None;
+        self.queue.complete_active(JobKind::Refine);
+        self.update_tray_state()?;
+        self.maybe_start_transcription()
+    }
+
+    /// Stop the transcription the tray is showing: the hotkey recording if one is
+    /// waiting, otherwise the running refine or auto job.
+    fn cancel_transcription(&mut self) {
+        let token = if self.recording.is_some() {
+            None
+        } else if self.hotkey_pending || self.queue.active_kind() == Some(JobKind::Hotkey) {
+            self.hotkey_cancel.as_ref()
+        } else if matches!(
+            self.queue.active_kind(),
+            Some(JobKind::Refine | JobKind::Auto)
+        ) {
+            self.background_cancel.as_ref()
+        } else {
+            None
+        };
+        match token {
+            Some(token) => {
+                tracing::info!("cancelling transcription");
+                token.cancel();
+            }
+            None => tracing::info!("nothing to cancel"),
+        }
+    }
+
+    fn handle_hotkey(&mut self, action: &HotkeyAction, state: HotKeyState) -> Result<()> {
+        let profile = match (action, state) {
+            (HotkeyAction::ToggleRecording, _) => RecordProfile::default(),
+            (HotkeyAction::Record(profile), _) => profile.clone(),
+            (HotkeyAction::Cancel, HotKeyState::Pressed) => return self.cancel(),
+            (HotkeyAction::RecopyLast, HotKeyState::Pressed) => return self.recopy_last(),
+            (HotkeyAction::Cancel | HotkeyAction::RecopyLast, HotKeyState::Released) => {
+                return Ok(());
+            }
+        };
+        let held = self.recording.as_ref().map(|_| {
+            self.recording_started
+                .map_or(Duration::MAX, |started| started.elapsed())
+        });
+        let min_held = Duration::from_millis(self.config.push_to_talk_min_ms);
+        match hotkeys::record_key_response(self.config.hotkey_mode, state, held, min_held) {
+            RecordKeyResponse::Toggle => self.toggle_recording(profile),
+            RecordKeyResponse::Stop => self.stop_recording(),
+            RecordKeyResponse::Discard => {
+                tracing::info!("push-to-talk press too short");
+                self.discard_recording()
+            }
+            RecordKeyResponse::Ignore => Ok(()),
+        }
+    }
+
+    /// Throw away the recording in progress, or stop the running transcription.
+    fn cancel(&mut self) -> Result<()> {
+        if self.recording.is_some() {
+            return self.discard_recording();
+        }
+        self.cancel_transcription();
+        Ok(())
+    }
+
+    fn recopy_last(&mut self) -> Result<()> {
+        let Some(text) = &self.last_transcript else {
+            tracing::info!("no transcript to copy yet");
+            return Ok(());
+        };
+        Clipboard::new()?.set_text(text)?;
+        tracing::info!("copied last transcript again");
+        Ok(())
+    }
+
+    fn toggle_recording(&mut self, profile: RecordProfile) -> Result<()> {
         if self.recording.is_some() {
             return self.stop_recording();
         }
@@ -438,23 +819,36 @@ impl App {
             tracing::info!("hotkey ignored while model is downloading");
             return Ok(());
         }
-        self.start_recording()
+        self.start_recording(profile)
     }
 
-    fn start_recording(&mut self) -> Result<()> {
+    fn start_recording(&mut self, profile: RecordProfile) -> Result<()> {
         if !self.queue.begin_hotkey_session() {
             tracing::info!("hotkey ignored while busy");
             return Ok(());
         }
-        tracing::info!("start recording");
+        tracing::info!(language = ?profile.language, "start recording");
+        self.hotkey_cancel = Some(CancelToken::new());
+        self.recording_profile = profile;
         self.play_beep();
         if self.config.selected_mic.is_none() {
             let current_default = CpalRecorder::default_device_name()?;
             self.tray.set_default_mic_label(current_default.as_deref());
         }
-        match CpalRecorder::start_recording(self.config.selected_mic.as_deref()) {
+        let journal = match storage::next_journal_path(&self.recordings_dir) {
+            Ok(path) => Some(path),
+            Err(err) => {
+                tracing::warn!(error = %err, "recording without a journal");
+                None
+            }
+        };
+        let started =
+            CpalRecorder::start_recording(self.config.selected_mic.as_deref(), journal.as_deref());
+        match started {
             Ok(handle) => {
+                self.live = self.start_live_session(&handle);
                 self.recording = Some(handle);
+                self.recording_started = Some(Instant::now());
                 self.update_tray_state()?;
                 Ok(())
             }
@@ -465,10 +859,58 @@ impl App {
         }
     }
 
+    fn start_live_session(&self, handle: &RecordingHandle) -> Option<LiveSession> {
+        if !self.config.live_transcription {
+            return None;
+        }
+        let transcriber = self.transcriber.clone()?;
+        let options = TranscribeOptions {
+            cancel: self.hotkey_cancel.clone().unwrap_or_default(),
+            ..self.hotkey_options(&self.recording_profile)
+        };
+        let worker_tx = self.worker_tx.clone();
+        Some(LiveSession::start(
+            handle.live_buffer(),
+            transcriber,
+            options,
+            move |text| {
+                let _ = worker_tx.send(WorkerEvent::LivePartial { text });
+            },
+        ))
+    }
+
+    /// Drop the recording in progress along with its journal.
+    fn discard_recording(&mut self) -> Result<()> {
+        tracing::info!("discarding recording");
+        let handle = self.recording.take().context("no recording in progress")?;
+        self.recording_started = None;
+        if let Some(token) = self.hotkey_cancel.take() {
+            token.cancel();
+        }
+        self.live = None;
+        let journal = handle.journal_path().map(Path::to_path_buf);
+        thread::spawn(move || {
+            if let Err(err) = handle.stop() {
+                tracing::warn!(error = %err, "failed to stop discarded recording");
+            }
+            if let Some(journal) = &journal {
+                remove_journal(journal);
+            }
+        });
+        self.queue.cancel_hotkey_session();
+        self.tray.set_live_preview(None)?;
+        self.update_tray_state()
+    }
+
     fn stop_recording(&mut self) -> Result<()> {
         tracing::info!("stop recording");
         let handle = self.recording.take().context("no recording in progress")?;
+        self.recording_started = None;
+        let profile = std::mem::take(&mut self.recording_profile);
+        let journal = handle.journal_path().map(Path::to_path_buf);
+        let live = self.live.take();
         let recordings_dir = self.recordings_dir.clone();
+        let format = self.config.recording_format;
         let worker_tx = self.worker_tx.clone();
         self.hotkey_pending = true;
         self.transcription_progress = None;
@@ -477,17 +919,52 @@ impl App {
         self.play_beep();
 
         thread::spawn(move || {
-            let result: Result<HotkeyJob> = (|| {
-                let recorded = handle.stop()?;
-                let (audio_path, text_path) = storage::next_recording_paths(&recordings_dir)?;
-                encode_m4a(&recorded, &audio_path)?;
-                Ok(HotkeyJob {
+            let result: Result<(HotkeyJob, Option<Result<Transcript>>)> = (|| {
+                let recorded = Arc::new(handle.stop()?);
+                let extension = format.extension();
+                let (audio_path, text_path) = match &journal {
+                    Some(journal) => storage::recording_paths_for_journal(journal, extension),
+                    None => storage::next_recording_paths(&recordings_dir, extension)?,
+                };
+                spawn_archive(
+                    Arc::clone(&recorded),
+                    audio_path.clone(),
+                    format,
+                    journal,
+                    worker_tx.clone(),
+                );
+                let transcript = live.map(|live| live.finish(Arc::clone(&recorded)));
+                let job = HotkeyJob {
                     audio_path,
                     text_path,
-                })
+                    audio: recorded,
+                    profile,
+                };
+                Ok((job, transcript))
             })();
+            // The live session only has the tail left to transcribe; if it fails,
+            // fall back to the regular queued job on the whole recording.
+            let result = result.map(|(job, transcript)| match transcript {
+                Some(Err(err)) if !err.is::<Cancelled>() => {
+                    tracing::warn!(error = %err, "live transcription failed");
+                    (job, None)
+                }
+                transcript => (job, transcript),
+            });
             match result {
-                Ok(job) => {
+                Ok((_, Some(Err(_)))) => {
+                    let _ = worker_tx.send(WorkerEvent::HotkeyTranscriptionCancelled);
+                }
+                Ok((job, Some(Ok(transcript)))) => {
+                    let event = match write_live_transcript(&job, &transcript) {
+                        Ok(()) => WorkerEvent::LiveTranscriptionDone {
+                            text: transcript.text(),
+                        },
+                        Err(err) => WorkerEvent::HotkeyRecordingError(err.to_string()),
+                    };
+                    let _ = worker_tx.send(event);
+                }
+                Ok((job, None)) => {
                     let _ = worker_tx.send(WorkerEvent::HotkeyRecordingReady(job));
                 }
                 Err(err) => {
@@ -498,6 +975,31 @@ impl App {
         Ok(())
     }
 
+    /// Encode each unfinished journal to audio and queue it for transcription.
+    fn recover_recordings(&mut self) -> Result<()> {
+        let journals = std::mem::take(&mut self.unfinished_journals);
+        self.tray.set_recoverable(0)?;
+        let format = self.config.recording_format;
+        let worker_tx = self.worker_tx.clone();
+        thread::spawn(move || {
+            for journal in journals {
+                match recover_journal(&journal, format) {
+                    Ok(Some(job)) => {
+                        let _ = worker_tx.send(WorkerEvent::RecordingRecovered(job));
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        let _ = worker_tx.send(WorkerEvent::Error(format!(
+                            "recover {}: {err:#}",
+                            journal.display()
+                        )));
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
     fn play_beep(&mut self) {
         if let Some(player) = self.beep.as_mut() {
             if let Err(err) = player.play() {
@@ -521,6 +1023,10 @@ impl App {
             input_path: spec.input_path.clone(),
             output_path,
             processed_path,
+            language: spec.language.unwrap_or_else(|| self.config.language.clone()),
+            translate: spec.translate.unwrap_or(self.config.translate),
+            vad: spec.vad.unwrap_or_else(|| self.config.vad.clone()),
+            split_channels: spec.split_channels,
         };
         self.auto_inflight.insert(spec.input_path);
         self.queue.enqueue_auto(job);
@@ -529,24 +1035,117 @@ impl App {
     }
 
     fn maybe_start_transcription(&mut self) -> Result<()> {
-        let model_path = match self.model_path.clone() {
-            Some(path) => path,
+        let mut transcriber = match self.transcriber.clone() {
+            Some(transcriber) => transcriber,
             None => return Ok(()),
         };
         let job = match self.queue.next_job() {
             Some(job) => job,
             None => return Ok(()),
         };
-        if matches!(job, Job::Hotkey(_)) {
-            self.hotkey_pending = false;
-        }
-        if let Job::Auto(_) = job {
-            let total = self.queue.auto_queue_len() + 1;
-            tracing::info!("auto transcription: processing 1 of {total}");
-        }
+        let options = match &job {
+            Job::Hotkey(job) => {
+                self.hotkey_pending = false;
+                if self.config.two_pass.is_some()
+                    && let Some(draft) = self.draft_transcriber.clone()
+                {
+                    tracing::info!("transcribing draft");
+                    transcriber = draft;
+                    self.draft_job = Some(job.clone());
+                }
+                TranscribeOptions {
+                    cancel: self.hotkey_cancel.get_or_insert_with(CancelToken::new).clone(),
+                    ..self.hotkey_options(&job.profile)
+                }
+            }
+            Job::Refine(job) => {
+                tracing::info!("refining draft");
+                TranscribeOptions {
+                    latency: LatencyMode::Accurate,
+                    threads: Some(self.background_threads()),
+                    cancel: self.background_cancel.insert(CancelToken::new()).clone(),
+                    ..self.hotkey_options(&job.profile)
+                }
+            }
+            Job::Auto(_) => {
+                let total = self.queue.auto_queue_len() + 1;
+                tracing::info!("auto transcription: processing 1 of {total}");
+                TranscribeOptions {
+                    threads: Some(self.background_threads()),
+                    cancel: self.background_cancel.insert(CancelToken::new()).clone(),
+                    ..self.transcribe_options()
+                }
+            }
+        };
         self.transcription_progress = None;
         self.update_tray_state()?;
-        spawn_transcription(job, model_path, self.vocabulary_prompt.clone(), self.worker_tx.clone());
+        spawn_transcription(job, transcriber, options, self.worker_tx.clone());
+        Ok(())
+    }
+
+    fn transcribe_options(&self) -> TranscribeOptions {
+        TranscribeOptions {
+            prompt: self.vocabulary_prompt.clone(),
+            language: Some(self.config.language.clone()),
+            translate: self.config.translate,
+            languages: self.config.languages.clone(),
+            preprocess: self.config.preprocess.clone(),
+            vad: self.config.vad.clone(),
+            split_channels: false,
+            filter: self.config.hallucination_filter.clone(),
+            device: self.config.device,
+            threads: self.config.threads,
+            retry_on_cpu: self.config.retry_on_cpu,
+            latency: self.config.latency,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// Options for a hotkey recording, with its `record` binding's overrides applied.
+    fn hotkey_options(&self, profile: &RecordProfile) -> TranscribeOptions {
+        let options = self.transcribe_options();
+        TranscribeOptions {
+            language: profile.language.clone().or(options.language),
+            translate: profile.translate.unwrap_or(options.translate),
+            ..options
+        }
+    }
+
+    /// Refine and auto jobs leave half the cores to hotkey recordings and the rest of
+    /// the system.
+    fn background_threads(&self) -> usize {
+        self.config
+            .background_threads
+            .filter(|&threads| threads > 0)
+            .unwrap_or_else(|| {
+                let cores = self
+                    .config
+                    .threads
+                    .filter(|&threads| threads > 0)
+                    .unwrap_or_else(|| {
+                        thread::available_parallelism()
+                            .map(|cores| cores.get())
+                            .unwrap_or(1)
+                    });
+                (cores / 2).max(1)
+            })
+    }
+
+    /// Keep one resident model per path; only a different model triggers a reload.
+    fn load_transcriber(&mut self, path: PathBuf) -> Result<()> {
+        if self.loaded_model.as_deref() == Some(path.as_path()) {
+            return Ok(());
+        }
+        let transcriber = Arc::new(WhisperTranscriber::new(path.clone())?);
+        let preload = Arc::clone(&transcriber);
+        let device = self.config.device;
+        thread::spawn(move || {
+            if let Err(err) = preload.preload(device) {
+                tracing::warn!(error = %err, "model preload failed");
+            }
+        });
+        self.transcriber = Some(transcriber);
+        self.loaded_model = Some(path);
         Ok(())
     }
 
@@ -601,69 +1200,271 @@ impl App {
 
 fn spawn_transcription(
     job: Job,
-    model_path: PathBuf,
-    prompt: Option<String>,
+    transcriber: Arc<dyn Transcriber>,
+    options: TranscribeOptions,
     tx: Sender<WorkerEvent>,
 ) {
     thread::spawn(move || match job {
         Job::Hotkey(job) => {
-            if let Err(err) = transcribe_hotkey(&job, model_path, prompt.as_deref(), tx.clone()) {
-                let _ = tx.send(WorkerEvent::HotkeyTranscriptionError(err.to_string()));
+            match transcribe_hotkey(&job, transcriber.as_ref(), &options, tx.clone()) {
+                Ok(()) => {}
+                Err(err) if err.is::<Cancelled>() => {
+                    let _ = tx.send(WorkerEvent::HotkeyTranscriptionCancelled);
+                }
+                Err(err) => {
+                    let _ = tx.send(WorkerEvent::HotkeyTranscriptionError(err.to_string()));
+                }
+            }
+        }
+        Job::Refine(job) => {
+            lower_thread_priority();
+            match transcribe_refine(&job, transcriber.as_ref(), &options, tx.clone()) {
+                Ok(()) => {}
+                Err(err) if err.is::<Cancelled>() => {
+                    let _ = tx.send(WorkerEvent::RefineCancelled);
+                }
+                Err(err) => {
+                    let _ = tx.send(WorkerEvent::RefineError(err.to_string()));
+                }
             }
         }
         Job::Auto(job) => {
-            if let Err(err) = transcribe_auto(&job, model_path, prompt.as_deref(), tx.clone()) {
-                let _ = tx.send(WorkerEvent::AutoTranscriptionError {
-                    input_path: job.input_path.clone(),
-                    error: err.to_string(),
-                });
+            lower_thread_priority();
+            let options = TranscribeOptions {
+                language: Some(job.language.clone()),
+                translate: job.translate,
+                vad: job.vad.clone(),
+                split_channels: job.split_channels,
+                ..options
+            };
+            match transcribe_auto(&job, transcriber.as_ref(), &options, tx.clone()) {
+                Ok(()) => {}
+                Err(err) if err.is::<Cancelled>() => {
+                    let _ = tx.send(WorkerEvent::AutoTranscriptionCancelled {
+                        input_path: job.input_path.clone(),
+                    });
+                }
+                Err(err) => {
+                    let _ = tx.send(WorkerEvent::AutoTranscriptionError {
+                        input_path: job.input_path.clone(),
+                        error: err.to_string(),
+                    });
+                }
             }
         }
     });
 }
 
+/// Run the calling thread, and the inference threads it starts, below interactive work.
+fn lower_thread_priority() {
+    #[cfg(target_os = "macos")]
+    {
+        let result = unsafe {
+            libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_UTILITY, 0)
+        };
+        if result != 0 {
+            tracing::warn!(code = result, "failed to lower thread priority");
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // On Linux the nice value is per thread, and threads inherit it.
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 10) } != 0 {
+            let error = std::io::Error::last_os_error();
+            tracing::warn!(error = %error, "failed to lower thread priority");
+        }
+    }
+}
+
+/// Finalize an interrupted recording; `None` when it captured nothing or was
+/// transcribed before its audio could be saved.
+fn recover_journal(journal: &Path, format: RecordingFormat) -> Result<Option<RefineJob>> {
+    let recorded = journal::read_journal(journal)?;
+    if recorded.samples.is_empty() {
+        remove_journal(journal);
+        return Ok(None);
+    }
+    let (audio_path, text_path) =
+        storage::recording_paths_for_journal(journal, format.extension());
+    let audio_path = save_recording(&recorded, &audio_path, format)?;
+    remove_journal(journal);
+    if text_path.exists() {
+        tracing::info!(path = %audio_path.display(), "recovered audio of a transcribed recording");
+        return Ok(None);
+    }
+    Ok(Some(RefineJob {
+        audio_path,
+        text_path,
+        draft: None,
+        audio: None,
+        profile: RecordProfile::default(),
+    }))
+}
+
+/// Save a finished recording in the background; transcription works from the
+/// samples in memory and does not wait for it. The journal is kept until the
+/// audio is written, as WAV if need be, so a failed save can still be recovered.
+fn spawn_archive(
+    recorded: Arc<RecordedAudio>,
+    audio_path: PathBuf,
+    format: RecordingFormat,
+    journal: Option<PathBuf>,
+    tx: Sender<WorkerEvent>,
+) {
+    thread::spawn(move || {
+        lower_thread_priority();
+        match save_recording(&recorded, &audio_path, format) {
+            Ok(_) => {
+                if let Some(journal) = &journal {
+                    remove_journal(journal);
+                }
+            }
+            Err(err) => {
+                let _ = tx.send(WorkerEvent::Error(format!(
+                    "save recording {}: {err:#}",
+                    audio_path.display()
+                )));
+            }
+        }
+    });
+}
+
+/// Save `recorded` in `format`, falling back to WAV next to it when that fails (m4a
+/// without ffmpeg, say), so a journal is never the only copy. Returns the path written.
+fn save_recording(
+    recorded: &RecordedAudio,
+    audio_path: &Path,
+    format: RecordingFormat,
+) -> Result<PathBuf> {
+    match encode_recording(recorded, audio_path, format) {
+        Ok(()) => Ok(audio_path.to_path_buf()),
+        Err(err) if format != RecordingFormat::Wav => {
+            let wav_path = audio_path.with_extension(RecordingFormat::Wav.extension());
+            tracing::warn!(
+                path = %audio_path.display(),
+                error = %format!("{err:#}"),
+                "cannot save recording in the configured format; saving WAV instead"
+            );
+            encode_wav(recorded, &wav_path)
+                .with_context(|| format!("save {} after: {err:#}", wav_path.display()))?;
+            Ok(wav_path)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn remove_journal(journal: &Path) {
+    if let Err(err) = fs::remove_file(journal) {
+        tracing::warn!(path = %journal.display(), error = %err, "failed to remove journal");
+    }
+}
+
 fn transcribe_hotkey(
     job: &HotkeyJob,
-    model_path: PathBuf,
-    prompt: Option<&str>,
+    transcriber: &dyn Transcriber,
+    options: &TranscribeOptions,
     tx: Sender<WorkerEvent>,
 ) -> Result<()> {
-    let transcriber = WhisperTranscriber::new(model_path)?;
-    let worker_progress = tx.clone();
-    let mut last_pct: Option<i32> = None;
-    let text = transcriber.transcribe_file_with_progress_and_prompt(
+    let text = transcribe_to_markdown(
+        Some(&job.audio),
         &job.audio_path,
-        Some(move |pct| {
-            if last_pct == Some(pct) {
-                return;
-            }
-            last_pct = Some(pct);
-            let _ = worker_progress.send(WorkerEvent::TranscriptionProgress(
-                pct.clamp(0, 100) as u8,
-            ));
-        }),
-        prompt,
-        None,
+        &job.text_path,
+        transcriber,
+        options,
+        tx.clone(),
     )?;
-    fs::write(&job.text_path, &text)
-        .with_context(|| format!("write transcript {}", job.text_path.display()))?;
     tx.send(WorkerEvent::HotkeyTranscriptionDone { text })
         .context("send transcription event")?;
     Ok(())
 }
 
+/// Second pass: overwrite the draft transcript with the larger model's.
+fn transcribe_refine(
+    job: &RefineJob,
+    transcriber: &dyn Transcriber,
+    options: &TranscribeOptions,
+    tx: Sender<WorkerEvent>,
+) -> Result<()> {
+    let text = transcribe_to_markdown(
+        job.audio.as_deref(),
+        &job.audio_path,
+        &job.text_path,
+        transcriber,
+        options,
+        tx.clone(),
+    )?;
+    tx.send(WorkerEvent::RefineDone {
+        text,
+        draft: job.draft.clone(),
+    })
+    .context("send refine event")?;
+    Ok(())
+}
+
+/// Transcribe `audio` when it is still in memory, or else the file at `audio_path`,
+/// and write the transcript as markdown.
+fn transcribe_to_markdown(
+    audio: Option<&RecordedAudio>,
+    audio_path: &Path,
+    text_path: &Path,
+    transcriber: &dyn Transcriber,
+    options: &TranscribeOptions,
+    worker_progress: Sender<WorkerEvent>,
+) -> Result<String> {
+    let mut last_pct: Option<i32> = None;
+    let progress: Progress = Box::new(move |pct| {
+        if last_pct == Some(pct) {
+            return;
+        }
+        last_pct = Some(pct);
+        let _ = worker_progress.send(WorkerEvent::TranscriptionProgress(
+            pct.clamp(0, 100) as u8,
+        ));
+    });
+    let transcript = match audio {
+        Some(recorded) => transcriber.transcribe_recording(recorded, Some(progress), options)?,
+        None => transcriber.transcribe(audio_path, Some(progress), options)?,
+    };
+    log_device(&transcript);
+    let markdown = output::render(&transcript, OutputFormat::Md)?;
+    fs::write(text_path, markdown)
+        .with_context(|| format!("write transcript {}", text_path.display()))?;
+    Ok(transcript.text())
+}
+
+/// Swap `draft` for `text` on the clipboard, unless something else was copied since.
+fn replace_clipboard_text(draft: &str, text: &str) -> Result<()> {
+    let mut clipboard = Clipboard::new()?;
+    if clipboard.get_text()? != draft {
+        tracing::info!("clipboard changed since the draft; leaving it");
+        return Ok(());
+    }
+    clipboard.set_text(text)
+}
+
+fn log_device(transcript: &Transcript) {
+    if let Some(device) = transcript.device {
+        tracing::info!(device = device.label(), "transcribed locally");
+    }
+}
+
+fn write_live_transcript(job: &HotkeyJob, transcript: &Transcript) -> Result<()> {
+    let markdown = output::render(transcript, OutputFormat::Md)?;
+    fs::write(&job.text_path, markdown)
+        .with_context(|| format!("write transcript {}", job.text_path.display()))
+}
+
 fn transcribe_auto(
     job: &AutoJob,
-    model_path: PathBuf,
-    prompt: Option<&str>,
+    transcriber: &dyn Transcriber,
+    options: &TranscribeOptions,
     tx: Sender<WorkerEvent>,
 ) -> Result<()> {
-    let transcriber = WhisperTranscriber::new(model_path)?;
     let worker_progress = tx.clone();
     let mut last_pct: Option<i32> = None;
-    let text = transcriber.transcribe_file_with_progress_and_prompt(
+    let transcript = transcriber.transcribe(
         &job.input_path,
-        Some(move |pct| {
+        Some(Box::new(move |pct| {
             if last_pct == Some(pct) {
                 return;
             }
@@ -671,17 +1472,18 @@ fn transcribe_auto(
             let _ = worker_progress.send(WorkerEvent::TranscriptionProgress(
                 pct.clamp(0, 100) as u8,
             ));
-        }),
-        prompt,
-        None,
+        })),
+        options,
     )?;
+    log_device(&transcript);
+    let markdown = output::render(&transcript, OutputFormat::Md)?;
     if let Some(parent) = job.output_path.parent() {
         storage::ensure_dir(parent)?;
     }
     if let Some(parent) = job.processed_path.parent() {
         storage::ensure_dir(parent)?;
     }
-    fs::write(&job.output_path, &text)
+    fs::write(&job.output_path, markdown)
         .with_context(|| format!("write transcript {}", job.output_path.display()))?;
     fs::rename(&job.input_path, &job.processed_path).with_context(|| {
         format!(
@@ -786,6 +1588,10 @@ fn enqueue_auto_path(path: &Path, watch: &WatchPair, tx: &Sender<WorkerEvent>) {
         input_path: path.to_path_buf(),
         output_dir: watch.output_dir.clone(),
         processed_dir: watch.processed_dir.clone(),
+        language: watch.language.clone(),
+        translate: watch.translate,
+        vad: watch.vad.clone(),
+        split_channels: watch.split_channels,
     };
     let _ = tx.send(WorkerEvent::AutoFileDetected(spec));
 }
@@ -813,6 +1619,51 @@ fn is_m4a(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Fail at startup rather than on the first job when a local model that has to
+/// translate is English-only: for hotkey recordings (and their drafts) or a watch.
+fn check_translation_models(config: &Config) -> Result<()> {
+    if config.remote.is_some() {
+        return Ok(());
+    }
+    let hotkeys_translate = config.translate
+        || config.hotkeys.iter().any(|binding| {
+            matches!(
+                &binding.action,
+                HotkeyAction::Record(RecordProfile { translate: Some(true), .. })
+            )
+        });
+    let watch_translates = config
+        .auto_transcribe
+        .iter()
+        .flat_map(|auto| &auto.watches)
+        .find(|watch| watch.translate.unwrap_or(config.translate));
+    if (hotkeys_translate || watch_translates.is_some())
+        && !model::supports_translation(&config.model)
+    {
+        let user = match watch_translates {
+            Some(watch) if !hotkeys_translate => {
+                format!("watch {}", watch.input_dir.display())
+            }
+            _ => "hotkey recordings".to_string(),
+        };
+        anyhow::bail!(
+            "model '{}' is English-only and cannot translate {user}; pick a multilingual model \
+             or turn off `translate`",
+            config.model
+        );
+    }
+    if hotkeys_translate
+        && let Some(two_pass) = &config.two_pass
+        && !model::supports_translation(&two_pass.draft_model)
+    {
+        anyhow::bail!(
+            "draft model '{}' is English-only and cannot translate hotkey recordings",
+            two_pass.draft_model
+        );
+    }
+    Ok(())
+}
+
 fn vocabulary_prompt(vocabulary: &[String]) -> Option<String> {
     let mut words = Vec::new();
     for word in vocabulary {
@@ -845,6 +1696,20 @@ fn spawn_model_download(models_dir: PathBuf, model: String, tx: Sender<WorkerEve
     });
 }
 
+fn spawn_draft_model_download(models_dir: PathBuf, model: String, tx: Sender<WorkerEvent>) {
+    thread::spawn(move || {
+        tracing::info!(model = %model, "ensuring draft model");
+        match model::ensure_model(&models_dir, &model) {
+            Ok(path) => {
+                let _ = tx.send(WorkerEvent::DraftModelReady(path));
+            }
+            Err(err) => {
+                let _ = tx.send(WorkerEvent::DraftModelError(err.to_string()));
+            }
+        }
+    });
+}
+
 fn default_models_dir() -> Result<PathBuf> {
     Ok(PathBuf::from(".models"))
 }
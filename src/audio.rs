@@ -1,12 +1,15 @@
+use crate::journal::JournalWriter;
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample, SampleFormat};
-use crossbeam_channel::{bounded, Sender};
-use std::io::Write;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use crossbeam_channel::{bounded, RecvTimeoutError, Sender};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+
+/// How often captured audio is appended to the recording's journal.
+const JOURNAL_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone)]
 pub struct AudioDevice {
@@ -24,6 +27,8 @@ pub struct RecordedAudio {
 pub struct RecordingHandle {
     stop_tx: Sender<()>,
     join: thread::JoinHandle<Result<RecordedAudio>>,
+    live: LiveBuffer,
+    journal: Option<PathBuf>,
 }
 
 impl RecordingHandle {
@@ -31,6 +36,48 @@ impl RecordingHandle {
         let _ = self.stop_tx.send(());
         self.join.join().unwrap_or_else(|_| Err(anyhow::anyhow!("recording thread panicked")))
     }
+
+    /// Read-only view of the audio captured so far, for transcribing while recording.
+    pub fn live_buffer(&self) -> LiveBuffer {
+        self.live.clone()
+    }
+
+    /// Journal the recording is streamed to; remove it once the audio is saved.
+    pub fn journal_path(&self) -> Option<&Path> {
+        self.journal.as_deref()
+    }
+}
+
+/// Shared view of an in-progress recording. The buffer is drained when the
+/// recording stops, so readers must switch to the returned `RecordedAudio` then.
+#[derive(Clone)]
+pub struct LiveBuffer {
+    samples: Arc<Mutex<Vec<f32>>>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl LiveBuffer {
+    pub fn frames(&self) -> usize {
+        let channels = self.channels.max(1) as usize;
+        self.samples
+            .lock()
+            .map(|samples| samples.len() / channels)
+            .unwrap_or(0)
+    }
+
+    /// Interleaved samples captured from `start_frame` onward.
+    pub fn read_from(&self, start_frame: usize) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        match self.samples.lock() {
+            Ok(samples) => {
+                let start = (start_frame * channels).min(samples.len());
+                let end = samples.len() - samples.len() % channels;
+                samples[start..end.max(start)].to_vec()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
 }
 
 pub struct CpalRecorder;
@@ -60,7 +107,12 @@ impl CpalRecorder {
         }
     }
 
-    pub fn start_recording(selected_device: Option<&str>) -> Result<RecordingHandle> {
+    /// Start capturing; with `journal`, samples are also streamed to that file so
+    /// the recording survives a crash.
+    pub fn start_recording(
+        selected_device: Option<&str>,
+        journal: Option<&Path>,
+    ) -> Result<RecordingHandle> {
         let host = cpal::default_host();
         let device = if let Some(name) = selected_device {
             host.input_devices()?
@@ -77,10 +129,25 @@ impl CpalRecorder {
         let sample_rate = config.sample_rate().0;
         let channels = config.channels();
         let sample_format = config.sample_format();
+        let journal = journal.and_then(|path| {
+            match JournalWriter::create(path, sample_rate, channels) {
+                Ok(writer) => Some((path.to_path_buf(), writer)),
+                Err(err) => {
+                    tracing::warn!(error = %err, "recording without a journal");
+                    None
+                }
+            }
+        });
+        let (journal_path, mut journal) = journal.unzip();
 
         let (stop_tx, stop_rx) = bounded(1);
+        let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+        let live = LiveBuffer {
+            samples: Arc::clone(&samples),
+            sample_rate,
+            channels,
+        };
         let join = thread::spawn(move || {
-            let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
             let samples_cb = Arc::clone(&samples);
             let err_fn = |err| tracing::error!(error = %err, "audio stream error");
 
@@ -112,8 +179,12 @@ impl CpalRecorder {
             };
 
             stream.play()?;
-            let _ = stop_rx.recv();
+            let mut journaled = 0;
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(JOURNAL_INTERVAL) {
+                append_to_journal(&mut journal, &samples, &mut journaled);
+            }
             drop(stream);
+            append_to_journal(&mut journal, &samples, &mut journaled);
 
             let data = std::mem::take(&mut *samples.lock().unwrap());
             Ok(RecordedAudio {
@@ -123,7 +194,12 @@ impl CpalRecorder {
             })
         });
 
-        Ok(RecordingHandle { stop_tx, join })
+        Ok(RecordingHandle {
+            stop_tx,
+            join,
+            live,
+            journal: journal_path,
+        })
     }
 }
 
@@ -139,49 +215,23 @@ where
     }
 }
 
-pub fn encode_m4a(recorded: &RecordedAudio, output: &Path) -> Result<()> {
-    let mut child = Command::new("ffmpeg")
-        .args([
-            "-y",
-            "-f",
-            "f32le",
-            "-ar",
-            &recorded.sample_rate.to_string(),
-            "-ac",
-            &recorded.channels.to_string(),
-            "-i",
-            "pipe:0",
-            "-c:a",
-            "aac",
-            "-b:a",
-            "128k",
-            output
-                .to_str()
-                .context("output path not valid utf-8")?,
-        ])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .context("spawn ffmpeg")?;
-
-    let mut stdin = child.stdin.take().context("open ffmpeg stdin")?;
-    let mut chunk = Vec::with_capacity(4096);
-    for sample in &recorded.samples {
-        chunk.extend_from_slice(&sample.to_le_bytes());
-        if chunk.len() >= 4096 {
-            stdin.write_all(&chunk)?;
-            chunk.clear();
-        }
-    }
-    if !chunk.is_empty() {
-        stdin.write_all(&chunk)?;
-    }
-    drop(stdin);
-
-    let status = child.wait().context("wait for ffmpeg")?;
-    if !status.success() {
-        return Err(anyhow::anyhow!("ffmpeg failed with status {status}"));
+/// Append samples captured since the last call; a failing journal is dropped
+/// rather than interrupting the recording.
+fn append_to_journal(
+    journal: &mut Option<JournalWriter>,
+    samples: &Mutex<Vec<f32>>,
+    journaled: &mut usize,
+) {
+    let Some(writer) = journal.as_mut() else {
+        return;
+    };
+    let pending = match samples.lock() {
+        Ok(samples) => samples[(*journaled).min(samples.len())..].to_vec(),
+        Err(_) => return,
+    };
+    *journaled += pending.len();
+    if let Err(err) = writer.append(&pending) {
+        tracing::warn!(error = %err, "journal write failed; continuing without it");
+        *journal = None;
     }
-    Ok(())
 }
@@ -1,3 +1,6 @@
+use crate::output::OutputFormat;
+use crate::transcriber::{Device, LatencyMode};
+use crate::vad::DetectorKind;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -42,4 +45,40 @@ pub struct TranscribeArgs {
     /// Force a language (e.g. "en", "ru"); default is English, use "auto" for detect.
     #[arg(long)]
     pub language: Option<String>,
+    /// Limit language detection to these codes (comma-separated, e.g. "en,ru").
+    #[arg(long, value_delimiter = ',')]
+    pub languages: Vec<String>,
+    /// Translate the speech to English instead of transcribing it.
+    #[arg(long)]
+    pub translate: bool,
+    /// Voice activity detector; overrides the config's `vad.detector`.
+    #[arg(long, value_enum)]
+    pub vad: Option<DetectorKind>,
+    /// Transcribe each channel separately and label them "Speaker 1", "Speaker 2", ...
+    #[arg(long)]
+    pub split_channels: bool,
+    /// Where to run inference; overrides the config's `device`.
+    #[arg(long, value_enum)]
+    pub device: Option<Device>,
+    /// Inference threads; defaults to every core.
+    #[arg(long)]
+    pub threads: Option<usize>,
+    /// Re-run on the CPU when the GPU returns no segments.
+    #[arg(long)]
+    pub retry_on_cpu: bool,
+    /// Decoder settings; `auto` takes the fast path for clips under 15 seconds.
+    #[arg(long, value_enum)]
+    pub latency: Option<LatencyMode>,
+    /// Transcribe on an OpenAI-compatible server at this base URL instead of locally.
+    #[arg(long)]
+    pub remote_url: Option<String>,
+    /// Transcript format to write; repeat for several. Defaults to md.
+    #[arg(long = "format", value_enum)]
+    pub formats: Vec<OutputFormat>,
+    /// Write the transcript to this file instead of next to the input (single format only).
+    #[arg(long, conflicts_with = "output_dir")]
+    pub output: Option<PathBuf>,
+    /// Directory for transcript files; defaults to the input file's directory.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
 }
@@ -12,4 +12,9 @@ impl Clipboard {
         clipboard.set_text(text.to_string()).context("set clipboard")?;
         Ok(())
     }
+
+    pub fn get_text(&mut self) -> Result<String> {
+        let mut clipboard = arboard::Clipboard::new().context("init clipboard")?;
+        clipboard.get_text().context("read clipboard")
+    }
 }
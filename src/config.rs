@@ -1,3 +1,10 @@
+use crate::encode::RecordingFormat;
+use crate::filter::HallucinationFilter;
+use crate::hotkeys::{HotkeyBinding, default_hotkeys};
+use crate::preprocess::PreprocessConfig;
+use crate::remote::RemoteConfig;
+use crate::transcriber::{Device, LatencyMode};
+use crate::vad::VadConfig;
 use anyhow::{Context, Result};
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
@@ -10,8 +17,73 @@ pub struct Config {
     pub selected_mic: Option<String>,
     pub model: String,
     pub recordings_dir: PathBuf,
+    /// Format hotkey recordings are saved in; only `m4a` needs ffmpeg.
+    pub recording_format: RecordingFormat,
     pub vocabulary: Vec<String>,
+    /// Language code spoken in recordings, or `auto` to detect it.
+    pub language: String,
+    /// Translate recordings to English instead of transcribing them.
+    pub translate: bool,
+    /// Language codes auto-detection may pick from; empty allows any.
+    pub languages: Vec<String>,
+    /// Optional DC removal, high-pass, noise gate and loudness normalization before VAD.
+    pub preprocess: PreprocessConfig,
+    pub vad: VadConfig,
+    /// Drops looping phrases, stock phrases and non-speech segments from transcripts.
+    pub hallucination_filter: HallucinationFilter,
     pub auto_transcribe: Option<AutoTranscribeConfig>,
+    /// Global shortcuts; defaults to Option+Space to record and Option+Esc to cancel.
+    pub hotkeys: Vec<HotkeyBinding>,
+    /// `toggle` starts and stops recording on each press; `push_to_talk` records
+    /// while the hotkey is held.
+    pub hotkey_mode: HotkeyMode,
+    /// Push-to-talk presses shorter than this are taken as accidental and discarded.
+    pub push_to_talk_min_ms: u64,
+    /// Transcribe hotkey recordings while they are still being captured.
+    pub live_transcription: bool,
+    /// Copy a quick draft from a small model, then refine it with `model`.
+    pub two_pass: Option<TwoPassConfig>,
+    /// Send audio to an OpenAI-compatible server instead of running whisper locally.
+    pub remote: Option<RemoteConfig>,
+    /// Where local inference runs: auto, cpu or gpu.
+    pub device: Device,
+    /// Inference threads; unset uses every core.
+    pub threads: Option<usize>,
+    /// Inference threads for refinement and watched-folder jobs; unset uses half of `threads`.
+    pub background_threads: Option<usize>,
+    /// Re-run inference on the CPU when the GPU returns nothing (doubles the cost of silence).
+    pub retry_on_cpu: bool,
+    /// Decoder settings: `auto` takes a fast greedy pass for clips under 15 seconds.
+    pub latency: LatencyMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyMode {
+    /// Press once to start recording and again to stop.
+    #[default]
+    Toggle,
+    /// Record while the hotkey is held down.
+    PushToTalk,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TwoPassConfig {
+    /// Model for the draft copied to the clipboard right away.
+    pub draft_model: String,
+    /// Replace the draft on the clipboard with the refined text, unless something
+    /// else has been copied since.
+    pub update_clipboard: bool,
+}
+
+impl Default for TwoPassConfig {
+    fn default() -> Self {
+        Self {
+            draft_model: "tiny".to_string(),
+            update_clipboard: false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,6 +96,18 @@ pub struct WatchPair {
     pub input_dir: PathBuf,
     pub output_dir: PathBuf,
     pub processed_dir: PathBuf,
+    /// Overrides the top-level `language` for files from this watch.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Overrides the top-level `translate` for files from this watch.
+    #[serde(default)]
+    pub translate: Option<bool>,
+    /// Overrides the top-level `vad` settings for files from this watch.
+    #[serde(default)]
+    pub vad: Option<VadConfig>,
+    /// Transcribe each channel as its own speaker (e.g. two-channel call recordings).
+    #[serde(default)]
+    pub split_channels: bool,
 }
 
 impl Default for Config {
@@ -32,8 +116,26 @@ impl Default for Config {
             selected_mic: None,
             model: "small".to_string(),
             recordings_dir: PathBuf::from(".recordings"),
+            recording_format: RecordingFormat::M4a,
             vocabulary: Vec::new(),
+            language: "en".to_string(),
+            translate: false,
+            languages: Vec::new(),
+            preprocess: PreprocessConfig::default(),
+            vad: VadConfig::default(),
+            hallucination_filter: HallucinationFilter::default(),
             auto_transcribe: None,
+            hotkeys: default_hotkeys(),
+            hotkey_mode: HotkeyMode::Toggle,
+            push_to_talk_min_ms: 300,
+            live_transcription: false,
+            two_pass: None,
+            remote: None,
+            device: Device::Auto,
+            threads: None,
+            background_threads: None,
+            retry_on_cpu: false,
+            latency: LatencyMode::Auto,
         }
     }
 }
@@ -79,6 +181,8 @@ impl ConfigStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hotkeys::{HotkeyAction, RecordProfile};
+    use crate::vad::DetectorKind;
     use tempfile::tempdir;
 
     #[test]
@@ -96,14 +200,97 @@ mod tests {
                 input_dir: PathBuf::from("input"),
                 output_dir: PathBuf::from("output"),
                 processed_dir: PathBuf::from("processed"),
+                language: Some("auto".to_string()),
+                translate: Some(true),
+                vad: Some(VadConfig {
+                    detector: DetectorKind::Spectral,
+                    ..VadConfig::default()
+                }),
+                split_channels: true,
             }],
         });
+        cfg.live_transcription = true;
+        cfg.recording_format = RecordingFormat::Flac;
+        cfg.hotkey_mode = HotkeyMode::PushToTalk;
+        cfg.push_to_talk_min_ms = 150;
+        cfg.hotkeys.push(HotkeyBinding::new(
+            "shift+alt+KeyR",
+            HotkeyAction::Record(RecordProfile {
+                language: Some("ru".to_string()),
+                translate: None,
+            }),
+        ));
+        cfg.two_pass = Some(TwoPassConfig {
+            update_clipboard: true,
+            ..TwoPassConfig::default()
+        });
+        cfg.language = "auto".to_string();
+        cfg.languages = vec!["en".to_string(), "ru".to_string()];
+        cfg.preprocess.highpass_hz = 120.0;
+        cfg.device = Device::Cpu;
+        cfg.threads = Some(6);
+        cfg.background_threads = Some(2);
+        cfg.latency = LatencyMode::Accurate;
+        cfg.hallucination_filter.blocklist = vec!["Subscribe to my channel".to_string()];
+        cfg.remote = Some(RemoteConfig {
+            base_url: "http://gpu-box:8000".to_string(),
+            ..RemoteConfig::default()
+        });
         store.save(&cfg)?;
         let loaded = store.load()?;
         assert_eq!(loaded.selected_mic, cfg.selected_mic);
         assert_eq!(loaded.model, cfg.model);
         assert_eq!(loaded.recordings_dir, cfg.recordings_dir);
+        assert_eq!(loaded.recording_format, RecordingFormat::Flac);
+        assert_eq!(loaded.hotkey_mode, HotkeyMode::PushToTalk);
+        assert_eq!(loaded.push_to_talk_min_ms, 150);
+        assert_eq!(loaded.hotkeys, cfg.hotkeys);
         assert_eq!(loaded.vocabulary, cfg.vocabulary);
+        assert!(loaded.live_transcription);
+        assert_eq!(loaded.two_pass, cfg.two_pass);
+        assert_eq!(loaded.language, "auto");
+        assert_eq!(loaded.languages, cfg.languages);
+        assert_eq!(loaded.remote, cfg.remote);
+        assert_eq!(loaded.preprocess, cfg.preprocess);
+        assert_eq!(loaded.device, Device::Cpu);
+        assert_eq!(loaded.threads, Some(6));
+        assert_eq!(loaded.background_threads, Some(2));
+        assert!(!loaded.retry_on_cpu);
+        assert_eq!(loaded.latency, LatencyMode::Accurate);
+        assert_eq!(loaded.hallucination_filter, cfg.hallucination_filter);
+        assert_eq!(
+            loaded
+                .auto_transcribe
+                .as_ref()
+                .and_then(|c| c.watches.first())
+                .and_then(|watch| watch.vad.as_ref())
+                .map(|vad| vad.detector),
+            Some(DetectorKind::Spectral)
+        );
+        assert_eq!(
+            loaded
+                .auto_transcribe
+                .as_ref()
+                .and_then(|c| c.watches.first())
+                .and_then(|watch| watch.translate),
+            Some(true)
+        );
+        assert_eq!(
+            loaded
+                .auto_transcribe
+                .as_ref()
+                .and_then(|c| c.watches.first())
+                .and_then(|watch| watch.language.as_deref()),
+            Some("auto")
+        );
+        assert_eq!(
+            loaded
+                .auto_transcribe
+                .as_ref()
+                .and_then(|c| c.watches.first())
+                .map(|watch| watch.split_channels),
+            Some(true)
+        );
         assert_eq!(
             loaded
                 .auto_transcribe
@@ -0,0 +1,361 @@
+use anyhow::{Context, Result};
+use rubato::{
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CodecRegistry, Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Input frames fed to the sinc resampler per call.
+const RESAMPLE_CHUNK: usize = 1024;
+
+/// Decodes an audio file one packet at a time into mono samples, so long files
+/// never have to be held in memory whole.
+pub struct AudioDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    channels: Option<usize>,
+    total_frames: Option<u64>,
+    /// Emit only this channel instead of the downmix.
+    channel: Option<usize>,
+    sample_buf: Option<SampleBuffer<f32>>,
+}
+
+impl AudioDecoder {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("open audio {}", path.display()))?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            mss,
+            // Gapless trims drop encoder delay and padding (MP3, Vorbis, Opus).
+            &FormatOptions {
+                enable_gapless: true,
+                ..FormatOptions::default()
+            },
+            &MetadataOptions::default(),
+        )?;
+        let format = probed.format;
+        let track = format.default_track().context("no default audio track")?;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .context("missing sample rate")?;
+        let track_id = track.id;
+        let channels = track.codec_params.channels.map(|channels| channels.count());
+        let total_frames = track.codec_params.n_frames;
+        let decoder = codecs().make(&track.codec_params, &DecoderOptions::default())?;
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+            sample_rate,
+            channels,
+            total_frames,
+            channel: None,
+            sample_buf: None,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Channel count from the container header, when it declares one.
+    pub fn channels(&self) -> Option<usize> {
+        self.channels
+    }
+
+    /// Decode a single channel (0-based) rather than averaging all of them.
+    pub fn select_channel(&mut self, channel: usize) {
+        self.channel = Some(channel);
+    }
+
+    /// Frame count from the container header, when it declares one.
+    pub fn total_frames(&self) -> Option<u64> {
+        self.total_frames
+    }
+
+    /// Decode the next packet of the default track; `None` at end of stream.
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<f32>>> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(None);
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = self.decoder.decode(&packet)?;
+            let spec = *decoded.spec();
+            let channels = spec.channels.count();
+            let frames = decoded.frames() as u64;
+            let sample_buf = match self.sample_buf.as_mut() {
+                Some(buf) if buf.capacity() as u64 >= frames * channels as u64 => buf,
+                _ => self
+                    .sample_buf
+                    .insert(SampleBuffer::<f32>::new(frames, spec)),
+            };
+            sample_buf.copy_interleaved_ref(decoded);
+            let samples = sample_buf.samples();
+            return Ok(Some(match self.channel {
+                Some(channel) => extract_channel(samples, channels, channel)?,
+                None => downmix_to_mono(samples, channels as u16),
+            }));
+        }
+    }
+}
+
+/// Symphonia's built-in decoders.
+#[cfg(not(feature = "opus"))]
+fn codecs() -> &'static CodecRegistry {
+    symphonia::default::get_codecs()
+}
+
+/// Symphonia's built-in decoders plus libopus for Ogg Opus recordings.
+#[cfg(feature = "opus")]
+fn codecs() -> &'static CodecRegistry {
+    static CODECS: std::sync::OnceLock<CodecRegistry> = std::sync::OnceLock::new();
+    CODECS.get_or_init(|| {
+        let mut registry = CodecRegistry::new();
+        symphonia::default::register_enabled_codecs(&mut registry);
+        registry.register_all::<crate::opus::OpusDecoder>();
+        registry
+    })
+}
+
+/// Average interleaved frames into a single channel.
+pub fn downmix_to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    if channels == 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+fn extract_channel(samples: &[f32], channels: usize, channel: usize) -> Result<Vec<f32>> {
+    if channel >= channels {
+        anyhow::bail!(
+            "channel {} out of range for {}-channel audio",
+            channel + 1,
+            channels
+        );
+    }
+    Ok(samples
+        .chunks_exact(channels)
+        .map(|frame| frame[channel])
+        .collect())
+}
+
+/// Sinc resampler to 16 kHz that accepts input in arbitrary pieces and keeps only
+/// one chunk of state, compensating for the filter delay so output stays aligned
+/// with the input timeline.
+pub struct StreamingResampler {
+    resampler: Option<SincFixedIn<f32>>,
+    ratio: f64,
+    pending: Vec<f32>,
+    input_total: usize,
+    output_total: usize,
+    delay_remaining: usize,
+}
+
+impl StreamingResampler {
+    pub fn new(sample_rate: u32) -> Result<Self> {
+        if sample_rate == 0 {
+            anyhow::bail!("invalid sample rate 0");
+        }
+        let ratio = 16_000.0 / sample_rate as f64;
+        let resampler = if sample_rate == 16_000 {
+            None
+        } else {
+            let params = SincInterpolationParameters {
+                sinc_len: 128,
+                f_cutoff: 0.95,
+                interpolation: SincInterpolationType::Linear,
+                oversampling_factor: 128,
+                window: WindowFunction::BlackmanHarris2,
+            };
+            Some(SincFixedIn::<f32>::new(
+                ratio,
+                1.0,
+                params,
+                RESAMPLE_CHUNK,
+                1,
+            )?)
+        };
+        let delay_remaining = resampler.as_ref().map(|r| r.output_delay()).unwrap_or(0);
+        Ok(Self {
+            resampler,
+            ratio,
+            pending: Vec::with_capacity(RESAMPLE_CHUNK * 2),
+            input_total: 0,
+            output_total: 0,
+            delay_remaining,
+        })
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>> {
+        self.input_total += input.len();
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(input.to_vec());
+        };
+        self.pending.extend_from_slice(input);
+        let mut out = Vec::new();
+        let mut consumed = 0;
+        while self.pending.len() - consumed >= resampler.input_frames_next() {
+            let needed = resampler.input_frames_next();
+            let chunk = &self.pending[consumed..consumed + needed];
+            let resampled = resampler.process(&[chunk], None)?;
+            consumed += needed;
+            out.extend(resampled.into_iter().next().unwrap_or_default());
+        }
+        self.pending.drain(..consumed);
+        Ok(self.emit(out))
+    }
+
+    /// Drain buffered input and the filter tail; call once after the last `process`.
+    pub fn flush(&mut self) -> Result<Vec<f32>> {
+        if self.resampler.is_none() {
+            return Ok(Vec::new());
+        }
+        let expected = (self.input_total as f64 * self.ratio).ceil() as usize;
+        let pending = std::mem::take(&mut self.pending);
+        let mut out = if pending.is_empty() {
+            Vec::new()
+        } else {
+            let resampled = self.process_partial(Some(pending))?;
+            self.emit(resampled)
+        };
+        while self.output_total < expected {
+            let resampled = self.process_partial(None)?;
+            if resampled.is_empty() {
+                break;
+            }
+            out.extend(self.emit(resampled));
+        }
+        let overshoot = self.output_total.saturating_sub(expected).min(out.len());
+        out.truncate(out.len() - overshoot);
+        self.output_total -= overshoot;
+        Ok(out)
+    }
+
+    fn process_partial(&mut self, input: Option<Vec<f32>>) -> Result<Vec<f32>> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(input.unwrap_or_default());
+        };
+        let resampled = match input {
+            Some(input) => resampler.process_partial(Some(&[input]), None)?,
+            None => resampler.process_partial::<Vec<f32>>(None, None)?,
+        };
+        Ok(resampled.into_iter().next().unwrap_or_default())
+    }
+
+    fn emit(&mut self, mut out: Vec<f32>) -> Vec<f32> {
+        if self.delay_remaining > 0 {
+            let skip = self.delay_remaining.min(out.len());
+            out.drain(..skip);
+            self.delay_remaining -= skip;
+        }
+        self.output_total += out.len();
+        out
+    }
+}
+
+pub fn resample_to_16k(input: Vec<f32>, sample_rate: u32) -> Result<Vec<f32>> {
+    if sample_rate == 16_000 {
+        return Ok(input);
+    }
+    let mut resampler = StreamingResampler::new(sample_rate)?;
+    let mut out = resampler.process(&input)?;
+    out.extend(resampler.flush()?);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sine(len: usize, sample_rate: u32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / sample_rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn streaming_resampler_output_does_not_depend_on_chunking() -> Result<()> {
+        let input = sine(48_000 + 517, 48_000);
+        let whole = resample_to_16k(input.clone(), 48_000)?;
+        assert_eq!(whole.len(), (input.len() as f64 / 3.0).ceil() as usize);
+
+        let mut resampler = StreamingResampler::new(48_000)?;
+        let mut pieces = Vec::new();
+        for chunk in input.chunks(333) {
+            pieces.extend(resampler.process(chunk)?);
+        }
+        pieces.extend(resampler.flush()?);
+        assert_eq!(pieces.len(), whole.len());
+        for (a, b) in pieces.iter().zip(&whole) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decoder_streams_downmixed_packets() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("stereo.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec)?;
+        for _ in 0..20_000 {
+            writer.write_sample(i16::MAX / 2)?;
+            writer.write_sample(0i16)?;
+        }
+        writer.finalize()?;
+
+        let mut decoder = AudioDecoder::open(&path)?;
+        assert_eq!(decoder.sample_rate(), 16_000);
+        assert_eq!(decoder.total_frames(), Some(20_000));
+        let mut packets = 0;
+        let mut frames = 0;
+        while let Some(chunk) = decoder.next_chunk()? {
+            packets += 1;
+            frames += chunk.len();
+            assert!(chunk.iter().all(|&s| (s - 0.25).abs() < 1e-3));
+        }
+        assert!(packets > 1);
+        assert_eq!(frames, 20_000);
+
+        let mut left = AudioDecoder::open(&path)?;
+        assert_eq!(left.channels(), Some(2));
+        left.select_channel(0);
+        let first = left.next_chunk()?.context("first packet")?;
+        assert!(first.iter().all(|&s| (s - 0.5).abs() < 1e-3));
+        Ok(())
+    }
+}
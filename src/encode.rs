@@ -0,0 +1,176 @@
+use crate::audio::RecordedAudio;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// How hotkey recordings are saved next to their transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    /// AAC, encoded by the `ffmpeg` CLI.
+    #[default]
+    M4a,
+    /// Uncompressed 16-bit PCM.
+    Wav,
+    /// Lossless 16-bit, about half the size of WAV.
+    Flac,
+    /// Ogg Opus, 16 kHz mono; needs a build with `--features opus`.
+    Opus,
+}
+
+impl RecordingFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            RecordingFormat::M4a => "m4a",
+            RecordingFormat::Wav => "wav",
+            RecordingFormat::Flac => "flac",
+            RecordingFormat::Opus => "opus",
+        }
+    }
+}
+
+/// Save `recorded` to `output` in `format`. Only m4a needs an external tool.
+pub fn encode_recording(
+    recorded: &RecordedAudio,
+    output: &Path,
+    format: RecordingFormat,
+) -> Result<()> {
+    match format {
+        RecordingFormat::M4a => encode_m4a(recorded, output),
+        RecordingFormat::Wav => encode_wav(recorded, output),
+        RecordingFormat::Flac => crate::flac::encode_flac(recorded, output),
+        #[cfg(feature = "opus")]
+        RecordingFormat::Opus => crate::opus::encode_opus(recorded, output),
+        #[cfg(not(feature = "opus"))]
+        RecordingFormat::Opus => {
+            anyhow::bail!("this build has no Opus encoder; rebuild with `--features opus`")
+        }
+    }
+}
+
+pub fn encode_wav(recorded: &RecordedAudio, output: &Path) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: recorded.channels,
+        sample_rate: recorded.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(output, spec)
+        .with_context(|| format!("create {}", output.display()))?;
+    for &sample in &recorded.samples {
+        writer.write_sample(quantize(sample))?;
+    }
+    writer
+        .finalize()
+        .with_context(|| format!("write {}", output.display()))
+}
+
+pub fn encode_m4a(recorded: &RecordedAudio, output: &Path) -> Result<()> {
+    let spawned = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-f",
+            "f32le",
+            "-ar",
+            &recorded.sample_rate.to_string(),
+            "-ac",
+            &recorded.channels.to_string(),
+            "-i",
+            "pipe:0",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            output.to_str().context("output path not valid utf-8")?,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) if err.kind() == ErrorKind::NotFound => anyhow::bail!(
+            "ffmpeg not found; install it or set `recording_format: flac` in the config"
+        ),
+        Err(err) => return Err(err).context("spawn ffmpeg"),
+    };
+
+    // Drain stderr while writing, so a chatty ffmpeg cannot stall on a full pipe.
+    let mut stderr = child.stderr.take().context("open ffmpeg stderr")?;
+    let stderr = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+    let mut stdin = child.stdin.take().context("open ffmpeg stdin")?;
+    let written = write_f32le(&mut stdin, &recorded.samples);
+    drop(stdin);
+
+    let status = child.wait().context("wait for ffmpeg")?;
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        anyhow::bail!("ffmpeg failed with {status}: {}", stderr.trim());
+    }
+    written.context("write samples to ffmpeg")
+}
+
+fn write_f32le(out: &mut impl Write, samples: &[f32]) -> std::io::Result<()> {
+    let mut chunk = Vec::with_capacity(4096);
+    for sample in samples {
+        chunk.extend_from_slice(&sample.to_le_bytes());
+        if chunk.len() >= 4096 {
+            out.write_all(&chunk)?;
+            chunk.clear();
+        }
+    }
+    out.write_all(&chunk)
+}
+
+/// Clamp to full scale and round to a 16-bit sample.
+pub(crate) fn quantize(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::AudioDecoder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn native_formats_decode_back_to_the_recording() -> Result<()> {
+        let samples: Vec<f32> = (0..16_000)
+            .map(|i| 0.25 * (i as f32 * 300.0 * std::f32::consts::TAU / 16_000.0).sin())
+            .collect();
+        let recorded = RecordedAudio {
+            samples: samples.clone(),
+            sample_rate: 16_000,
+            channels: 1,
+        };
+        let dir = tempdir()?;
+        for format in [RecordingFormat::Wav, RecordingFormat::Flac] {
+            let path = dir.path().join(format!("recording.{}", format.extension()));
+            encode_recording(&recorded, &path, format)?;
+            let mut decoder = AudioDecoder::open(&path)?;
+            assert_eq!(decoder.sample_rate(), 16_000);
+            let mut decoded = Vec::new();
+            while let Some(chunk) = decoder.next_chunk()? {
+                decoded.extend(chunk);
+            }
+            assert_eq!(decoded.len(), samples.len(), "{format:?}");
+            let worst = decoded
+                .iter()
+                .zip(&samples)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0f32, f32::max);
+            assert!(worst < 1e-4, "{format:?} differs by {worst}");
+        }
+        Ok(())
+    }
+}
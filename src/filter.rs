@@ -0,0 +1,248 @@
+use crate::transcript::{Segment, Transcript};
+use serde::{Deserialize, Serialize};
+
+/// Post-inference cleanup for text whisper invents on silence or noise: looping
+/// phrases, low-confidence segments it flagged as non-speech, and stock phrases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallucinationFilter {
+    pub enabled: bool,
+    /// A phrase or segment may repeat back-to-back this many times. Longer loops
+    /// inside a segment are cut to one copy; further identical segments are dropped.
+    pub max_repeats: usize,
+    /// Longest phrase, in words, checked for loops.
+    pub max_ngram: usize,
+    /// Segments above this no-speech probability are dropped when their mean
+    /// token probability is also below `min_avg_token_prob`. Remote servers report
+    /// the probability; for local inference it is the share of the segment's span
+    /// the voice activity detector heard no speech in.
+    pub max_no_speech_prob: f32,
+    pub min_avg_token_prob: f32,
+    /// Segments consisting only of one of these phrases are dropped; matching
+    /// ignores case and punctuation.
+    pub blocklist: Vec<String>,
+}
+
+impl Default for HallucinationFilter {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_repeats: 3,
+            max_ngram: 8,
+            max_no_speech_prob: 0.6,
+            // exp(-1.0): whisper's own log-probability fallback threshold.
+            min_avg_token_prob: 0.37,
+            blocklist: [
+                "Thank you for watching",
+                "Thanks for watching",
+                "Please subscribe",
+                "Like and subscribe",
+                "Subtitles by the Amara.org community",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+impl HallucinationFilter {
+    /// Drop or shorten hallucinated segments in place, logging what was removed.
+    pub fn apply(&self, transcript: &mut Transcript) {
+        if !self.enabled {
+            return;
+        }
+        let blocklist: Vec<String> = self
+            .blocklist
+            .iter()
+            .map(|phrase| normalize(phrase))
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        let mut kept: Vec<Segment> = Vec::with_capacity(transcript.segments.len());
+        let mut previous: Option<String> = None;
+        let mut run = 0;
+        for mut segment in std::mem::take(&mut transcript.segments) {
+            if let Some(reason) = self.drop_reason(&segment, &blocklist) {
+                log_dropped(reason, &segment, segment.text.trim());
+                continue;
+            }
+            let normalized = normalize(&segment.text);
+            if !normalized.is_empty() && previous.as_deref() == Some(normalized.as_str()) {
+                run += 1;
+                if run >= self.max_repeats {
+                    log_dropped("repeated segment", &segment, segment.text.trim());
+                    continue;
+                }
+            } else {
+                run = 0;
+            }
+            previous = Some(normalized);
+            self.collapse_loops(&mut segment);
+            kept.push(segment);
+        }
+        transcript.segments = kept;
+    }
+
+    fn drop_reason(&self, segment: &Segment, blocklist: &[String]) -> Option<&'static str> {
+        if let Some(no_speech_prob) = segment.no_speech_prob
+            && no_speech_prob > self.max_no_speech_prob
+            && segment.avg_token_prob < self.min_avg_token_prob
+        {
+            return Some("no speech");
+        }
+        let normalized = normalize(&segment.text);
+        if !normalized.is_empty() && blocklist.contains(&normalized) {
+            return Some("blocklisted phrase");
+        }
+        None
+    }
+
+    /// Collapse phrases repeated more than `max_repeats` times in a row within a segment.
+    fn collapse_loops(&self, segment: &mut Segment) {
+        let tokens: Vec<&str> = segment.text.split_whitespace().collect();
+        let keep = loop_mask(&tokens, self.max_ngram, self.max_repeats);
+        if keep.iter().all(|&keep| keep) {
+            return;
+        }
+        let dropped: Vec<&str> = tokens
+            .iter()
+            .zip(&keep)
+            .filter(|(_, keep)| !**keep)
+            .map(|(token, _)| *token)
+            .collect();
+        log_dropped("repeated phrase", segment, &dropped.join(" "));
+        let text: Vec<&str> = tokens
+            .iter()
+            .zip(&keep)
+            .filter(|(_, keep)| **keep)
+            .map(|(token, _)| *token)
+            .collect();
+        if segment.words.len() == tokens.len() {
+            let mut keep = keep.iter();
+            segment.words.retain(|_| *keep.next().unwrap_or(&true));
+        } else {
+            // Word timings no longer line up with the text; renderers fall back to it.
+            segment.words.clear();
+        }
+        segment.text = format!(" {}", text.join(" "));
+    }
+}
+
+/// Mark tokens that belong to the second and later copies of an n-gram repeated
+/// more than `max_repeats` times back-to-back.
+fn loop_mask(tokens: &[&str], max_ngram: usize, max_repeats: usize) -> Vec<bool> {
+    let mut keep = vec![true; tokens.len()];
+    let words: Vec<String> = tokens.iter().map(|token| normalize(token)).collect();
+    let mut start = 0;
+    while start < words.len() {
+        let mut advanced = false;
+        for n in 1..=max_ngram.min((words.len() - start) / 2) {
+            let gram = &words[start..start + n];
+            let mut copies = 1;
+            while start + (copies + 1) * n <= words.len()
+                && words[start + copies * n..start + (copies + 1) * n] == *gram
+            {
+                copies += 1;
+            }
+            if copies > max_repeats {
+                for flag in &mut keep[start + n..start + copies * n] {
+                    *flag = false;
+                }
+                start += copies * n;
+                advanced = true;
+                break;
+            }
+        }
+        if !advanced {
+            start += 1;
+        }
+    }
+    keep
+}
+
+/// Lowercase and drop punctuation, so "Thanks for watching!" matches "thanks for watching".
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|ch| ch.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn log_dropped(reason: &str, segment: &Segment, text: &str) {
+    tracing::debug!(
+        reason,
+        start_ms = segment.start_ms,
+        end_ms = segment.end_ms,
+        text = %text,
+        "dropped hallucinated text"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, text: &str) -> Segment {
+        Segment {
+            start_ms,
+            end_ms: start_ms + 1_000,
+            text: text.to_string(),
+            avg_token_prob: 0.9,
+            no_speech_prob: None,
+            speaker: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn collapses_phrase_loops_and_repeated_segments() {
+        let mut transcript = Transcript::new(vec![
+            segment(
+                0,
+                " I think we should, we should, we should, we should, we should go.",
+            ),
+            segment(1_000, " Okay."),
+            segment(2_000, " Okay."),
+            segment(3_000, " Okay."),
+            segment(4_000, " Okay."),
+            segment(5_000, " Okay."),
+            segment(6_000, " Bye bye."),
+        ]);
+        HallucinationFilter::default().apply(&mut transcript);
+        assert_eq!(
+            transcript.text(),
+            "I think we should, go. Okay. Okay. Okay. Bye bye."
+        );
+    }
+
+    #[test]
+    fn drops_silent_low_confidence_and_blocklisted_segments() {
+        let mut unsure = segment(0, " Hmm.");
+        unsure.no_speech_prob = Some(0.9);
+        unsure.avg_token_prob = 0.2;
+        let mut confident = segment(1_000, " Yes.");
+        confident.no_speech_prob = Some(0.9);
+        let mut transcript = Transcript::new(vec![
+            unsure,
+            confident,
+            segment(2_000, " Thank you for watching!"),
+            segment(3_000, " Thank you for watching my talk."),
+        ]);
+        HallucinationFilter::default().apply(&mut transcript);
+        assert_eq!(transcript.text(), "Yes. Thank you for watching my talk.");
+
+        let mut transcript = Transcript::new(vec![segment(0, " Thank you for watching!")]);
+        HallucinationFilter {
+            enabled: false,
+            ..HallucinationFilter::default()
+        }
+        .apply(&mut transcript);
+        assert_eq!(transcript.segments.len(), 1);
+    }
+}
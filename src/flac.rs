@@ -0,0 +1,317 @@
+use crate::audio::RecordedAudio;
+use crate::encode::quantize;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Samples per channel in every frame but the last.
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+/// Highest order of FLAC's fixed polynomial predictors.
+const MAX_FIXED_ORDER: usize = 4;
+/// Largest Rice parameter; 15 is reserved as an escape code.
+const MAX_RICE_PARAM: u32 = 14;
+
+/// Write `recorded` as 16-bit FLAC. Each channel is coded on its own with the best
+/// fixed predictor, which gets most of FLAC's compression on speech.
+pub fn encode_flac(recorded: &RecordedAudio, output: &Path) -> Result<()> {
+    let channels = recorded.channels as usize;
+    if !(1..=8).contains(&channels) {
+        anyhow::bail!("FLAC supports 1 to 8 channels, got {channels}");
+    }
+    if recorded.sample_rate == 0 || recorded.sample_rate >= 1 << 20 {
+        anyhow::bail!(
+            "FLAC cannot store a {} Hz sample rate",
+            recorded.sample_rate
+        );
+    }
+    let frames = recorded.samples.len() / channels;
+    let file = File::create(output).with_context(|| format!("create {}", output.display()))?;
+    let mut out = BufWriter::new(file);
+    out.write_all(b"fLaC")?;
+    out.write_all(&stream_info(recorded.sample_rate, channels, frames as u64))?;
+    let samples = &recorded.samples[..frames * channels];
+    for (index, block) in samples.chunks(BLOCK_SIZE * channels).enumerate() {
+        out.write_all(&encode_frame(block, channels, index as u64))?;
+    }
+    out.flush()
+        .with_context(|| format!("write {}", output.display()))
+}
+
+/// The mandatory STREAMINFO block, marked as the last metadata block.
+fn stream_info(sample_rate: u32, channels: usize, frames: u64) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(1, 1);
+    bits.write(0, 7);
+    bits.write(34, 24);
+    bits.write(BLOCK_SIZE as u64, 16);
+    bits.write(BLOCK_SIZE as u64, 16);
+    // Frame sizes and the MD5 signature are optional; zero means unknown.
+    bits.write(0, 24);
+    bits.write(0, 24);
+    bits.write(sample_rate.into(), 20);
+    bits.write(channels as u64 - 1, 3);
+    bits.write(u64::from(BITS_PER_SAMPLE - 1), 5);
+    bits.write(frames, 36);
+    bits.write(0, 64);
+    bits.write(0, 64);
+    bits.into_bytes()
+}
+
+fn encode_frame(block: &[f32], channels: usize, index: u64) -> Vec<u8> {
+    let len = block.len() / channels;
+    let mut bits = BitWriter::default();
+    bits.write(0b11_1111_1111_1110, 14);
+    // Reserved bit, then fixed block size.
+    bits.write(0, 2);
+    // Block size in 16 bits after the frame number; sample rate from STREAMINFO.
+    bits.write(0b0111, 4);
+    bits.write(0, 4);
+    // Independent channels, 16 bits per sample, reserved bit.
+    bits.write(channels as u64 - 1, 4);
+    bits.write(0b100, 3);
+    bits.write(0, 1);
+    write_utf8(&mut bits, index);
+    bits.write(len as u64 - 1, 16);
+    let crc = crc8(&bits.bytes);
+    bits.write(crc.into(), 8);
+    for channel in 0..channels {
+        let samples: Vec<i32> = block
+            .iter()
+            .skip(channel)
+            .step_by(channels)
+            .map(|&sample| quantize(sample).into())
+            .collect();
+        write_subframe(&mut bits, &samples);
+    }
+    bits.pad_to_byte();
+    let crc = crc16(&bits.bytes);
+    bits.write(crc.into(), 16);
+    bits.into_bytes()
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&sample| sample == samples[0]) {
+        bits.write(0b0000_0000, 8);
+        bits.write(samples[0] as u64, BITS_PER_SAMPLE);
+        return;
+    }
+    let (order, residual) = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| (order, fixed_residual(samples, order)))
+        .min_by_key(|(_, residual)| {
+            residual
+                .iter()
+                .map(|r| u64::from(r.unsigned_abs()))
+                .sum::<u64>()
+        })
+        .expect("order 0 always applies");
+    let rice = rice_parameter(&residual);
+    let predicted_bits =
+        (order as u64 * u64::from(BITS_PER_SAMPLE)) + 10 + rice_bits(&residual, rice);
+    if predicted_bits >= samples.len() as u64 * u64::from(BITS_PER_SAMPLE) {
+        bits.write(0b0000_0010, 8);
+        for &sample in samples {
+            bits.write(sample as u64, BITS_PER_SAMPLE);
+        }
+        return;
+    }
+    bits.write(0b0001_0000 | (order as u64) << 1, 8);
+    for &sample in &samples[..order] {
+        bits.write(sample as u64, BITS_PER_SAMPLE);
+    }
+    // Rice coding with 4-bit parameters and a single partition.
+    bits.write(0, 2);
+    bits.write(0, 4);
+    bits.write(rice.into(), 4);
+    for &value in &residual {
+        let value = zigzag(value);
+        bits.write_unary(value >> rice);
+        bits.write(value.into(), rice);
+    }
+}
+
+/// Prediction error of the fixed polynomial predictor of `order`.
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |back: usize| samples[i - back];
+            let predicted = match order {
+                0 => 0,
+                1 => s(1),
+                2 => 2 * s(1) - s(2),
+                3 => 3 * s(1) - 3 * s(2) + s(3),
+                _ => 4 * s(1) - 6 * s(2) + 4 * s(3) - s(4),
+            };
+            samples[i] - predicted
+        })
+        .collect()
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Rice parameter close to log2 of the mean folded residual.
+fn rice_parameter(residual: &[i32]) -> u32 {
+    let sum: u64 = residual.iter().map(|&r| u64::from(zigzag(r))).sum();
+    let mean = sum / residual.len().max(1) as u64;
+    (u64::BITS - mean.leading_zeros())
+        .saturating_sub(1)
+        .min(MAX_RICE_PARAM)
+}
+
+fn rice_bits(residual: &[i32], rice: u32) -> u64 {
+    residual
+        .iter()
+        .map(|&r| u64::from(zigzag(r) >> rice) + 1 + u64::from(rice))
+        .sum()
+}
+
+/// FLAC's frame number coding: UTF-8's scheme extended to 36 bits.
+fn write_utf8(bits: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        bits.write(value, 8);
+        return;
+    }
+    let mut len = 2;
+    while value >> (5 * len + 1) != 0 {
+        len += 1;
+    }
+    let lead = (0xFF00u64 >> len) & 0xFF;
+    bits.write(lead | (value >> (6 * (len - 1))), 8);
+    for shift in (0..len - 1).rev() {
+        bits.write(0x80 | ((value >> (6 * shift)) & 0x3F), 8);
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in bytes {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// MSB-first bit packer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    /// Append the low `bits` bits of `value`.
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits > 32 {
+            self.write(value >> 32, bits - 32);
+            self.write(value, 32);
+            return;
+        }
+        self.pending = (self.pending << bits) | (value & ((1u64 << bits) - 1));
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes.push((self.pending >> self.pending_bits) as u8);
+        }
+        self.pending &= (1u64 << self.pending_bits) - 1;
+    }
+
+    /// `value` zeros followed by a one.
+    fn write_unary(&mut self, mut value: u32) {
+        while value >= 32 {
+            self.write(0, 32);
+            value -= 32;
+        }
+        self.write(1, value + 1);
+    }
+
+    fn pad_to_byte(&mut self) {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.pad_to_byte();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::AudioDecoder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn flac_round_trips_losslessly_through_symphonia() -> Result<()> {
+        // Two blocks and a short tail: a tone on the left, silence then noise on the right.
+        let frames = BLOCK_SIZE * 2 + 100;
+        let mut samples = Vec::with_capacity(frames * 2);
+        let mut noise = 1u32;
+        for i in 0..frames {
+            samples.push(0.5 * (i as f32 * 440.0 * std::f32::consts::TAU / 48_000.0).sin());
+            noise = noise.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let right = if i < BLOCK_SIZE {
+                0.0
+            } else {
+                (noise >> 8) as f32 / (1 << 24) as f32 - 0.5
+            };
+            samples.push(right);
+        }
+        let recorded = RecordedAudio {
+            samples,
+            sample_rate: 48_000,
+            channels: 2,
+        };
+        let dir = tempdir()?;
+        let path = dir.path().join("recording.flac");
+        encode_flac(&recorded, &path)?;
+
+        for channel in 0..2 {
+            let mut decoder = AudioDecoder::open(&path)?;
+            assert_eq!(decoder.sample_rate(), 48_000);
+            assert_eq!(decoder.total_frames(), Some(frames as u64));
+            decoder.select_channel(channel);
+            let mut decoded = Vec::new();
+            while let Some(chunk) = decoder.next_chunk()? {
+                decoded.extend(chunk);
+            }
+            let expected: Vec<i16> = recorded.samples[channel..]
+                .iter()
+                .step_by(2)
+                .map(|&sample| quantize(sample))
+                .collect();
+            let decoded: Vec<i16> = decoded
+                .iter()
+                .map(|&sample| (sample * 32_768.0).round() as i16)
+                .collect();
+            assert_eq!(decoded, expected, "channel {channel}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::config::HotkeyMode;
+use anyhow::{Context, Result};
+use global_hotkey::HotKeyState;
+use global_hotkey::hotkey::HotKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// A global shortcut and what it does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    /// Accelerator such as `alt+Space` or `shift+alt+KeyR`; modifiers come first.
+    pub keys: String,
+    #[serde(flatten)]
+    pub action: HotkeyAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Start or stop a recording with the top-level settings (held in push-to-talk mode).
+    ToggleRecording,
+    /// Discard the recording in progress, or stop the running transcription.
+    Cancel,
+    /// Like `toggle_recording`, with its own language or translation setting.
+    Record(RecordProfile),
+    /// Copy the last hotkey transcript to the clipboard again.
+    RecopyLast,
+}
+
+/// Transcription settings a `record` binding applies to its recordings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordProfile {
+    /// Language code, or `auto` to detect; unset keeps the default.
+    pub language: Option<String>,
+    /// Overrides the top-level `translate`.
+    pub translate: Option<bool>,
+}
+
+impl HotkeyBinding {
+    pub fn new(keys: &str, action: HotkeyAction) -> Self {
+        Self {
+            keys: keys.to_string(),
+            action,
+        }
+    }
+
+    /// The accelerator in macOS menu style, e.g. `Option+Space`.
+    pub fn label(&self) -> String {
+        accelerator_label(&self.keys)
+    }
+}
+
+/// What a press or release of a recording hotkey does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKeyResponse {
+    /// Start a recording, or stop the one in progress.
+    Toggle,
+    Stop,
+    /// Drop the recording without transcribing it.
+    Discard,
+    Ignore,
+}
+
+/// Decide what a recording hotkey event does in `mode`. `held` is how long the
+/// current recording has run, `None` when nothing is recording; push-to-talk
+/// recordings released before `min_held` are taken as accidental.
+pub fn record_key_response(
+    mode: HotkeyMode,
+    state: HotKeyState,
+    held: Option<Duration>,
+    min_held: Duration,
+) -> RecordKeyResponse {
+    match (mode, state, held) {
+        (HotkeyMode::Toggle, HotKeyState::Pressed, _) => RecordKeyResponse::Toggle,
+        // Held keys may repeat the press; only the first one starts recording.
+        (HotkeyMode::PushToTalk, HotKeyState::Pressed, None) => RecordKeyResponse::Toggle,
+        (HotkeyMode::PushToTalk, HotKeyState::Released, Some(held)) if held < min_held => {
+            RecordKeyResponse::Discard
+        }
+        (HotkeyMode::PushToTalk, HotKeyState::Released, Some(_)) => RecordKeyResponse::Stop,
+        _ => RecordKeyResponse::Ignore,
+    }
+}
+
+/// Option+Space records and Option+Esc cancels, as before bindings were configurable.
+pub fn default_hotkeys() -> Vec<HotkeyBinding> {
+    vec![
+        HotkeyBinding::new("alt+Space", HotkeyAction::ToggleRecording),
+        HotkeyBinding::new("alt+Escape", HotkeyAction::Cancel),
+    ]
+}
+
+/// Parse every binding's accelerator, rejecting the same keys bound twice.
+pub fn parse_bindings(bindings: &[HotkeyBinding]) -> Result<Vec<(HotKey, HotkeyAction)>> {
+    let mut seen = HashSet::new();
+    let mut parsed = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        let hotkey: HotKey = binding
+            .keys
+            .parse()
+            .with_context(|| format!("invalid hotkey `{}`", binding.keys))?;
+        if !seen.insert(hotkey.id()) {
+            anyhow::bail!("hotkey `{}` is bound more than once", binding.keys);
+        }
+        parsed.push((hotkey, binding.action.clone()));
+    }
+    Ok(parsed)
+}
+
+/// Label of the first binding for `action`, for menu items.
+pub fn label_for(bindings: &[HotkeyBinding], action: &HotkeyAction) -> Option<String> {
+    bindings
+        .iter()
+        .find(|binding| &binding.action == action)
+        .map(HotkeyBinding::label)
+}
+
+fn accelerator_label(keys: &str) -> String {
+    keys.split('+')
+        .map(|token| {
+            let token = token.trim();
+            match token.to_ascii_lowercase().as_str() {
+                "alt" | "option" => "Option".to_string(),
+                "super" | "cmd" | "command" | "cmdorctrl" | "commandorcontrol" => {
+                    "Command".to_string()
+                }
+                "ctrl" | "control" => "Control".to_string(),
+                "shift" => "Shift".to_string(),
+                "escape" | "esc" => "Esc".to_string(),
+                _ => {
+                    let key = token
+                        .strip_prefix("Key")
+                        .or_else(|| token.strip_prefix("Digit"))
+                        .filter(|key| key.len() == 1)
+                        .unwrap_or(token);
+                    let mut chars = key.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use global_hotkey::hotkey::{Code, Modifiers};
+
+    #[test]
+    fn bindings_parse_from_yaml_with_their_actions() -> Result<()> {
+        let yaml = "
+- keys: alt+Space
+  action: toggle_recording
+- keys: shift+alt+KeyR
+  action: record
+  language: ru
+  translate: false
+- keys: alt+Escape
+  action: cancel
+- keys: alt+KeyV
+  action: recopy_last
+";
+        let bindings: Vec<HotkeyBinding> = serde_yaml::from_str(yaml)?;
+        assert_eq!(
+            bindings[1].action,
+            HotkeyAction::Record(RecordProfile {
+                language: Some("ru".to_string()),
+                translate: Some(false),
+            })
+        );
+        let parsed = parse_bindings(&bindings)?;
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed[0].0, HotKey::new(Some(Modifiers::ALT), Code::Space));
+        assert_eq!(parsed[3].1, HotkeyAction::RecopyLast);
+        assert_eq!(
+            label_for(&bindings, &HotkeyAction::Cancel).as_deref(),
+            Some("Option+Esc")
+        );
+        assert_eq!(bindings[1].label(), "Shift+Option+R");
+        assert_eq!(
+            HotkeyBinding::new("cmd+shift+1", HotkeyAction::Cancel).label(),
+            "Command+Shift+1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn push_to_talk_records_while_held() {
+        let min = Duration::from_millis(300);
+        let ptt = |state, held| record_key_response(HotkeyMode::PushToTalk, state, held, min);
+        assert_eq!(ptt(HotKeyState::Pressed, None), RecordKeyResponse::Toggle);
+        // Key repeat while held must not stop the recording it started.
+        let repeat = ptt(HotKeyState::Pressed, Some(Duration::from_millis(50)));
+        assert_eq!(repeat, RecordKeyResponse::Ignore);
+        let tap = ptt(HotKeyState::Released, Some(Duration::from_millis(120)));
+        assert_eq!(tap, RecordKeyResponse::Discard);
+        assert_eq!(ptt(HotKeyState::Released, Some(min)), RecordKeyResponse::Stop);
+        assert_eq!(ptt(HotKeyState::Released, None), RecordKeyResponse::Ignore);
+
+        let toggle = |state, held| record_key_response(HotkeyMode::Toggle, state, held, min);
+        assert_eq!(toggle(HotKeyState::Pressed, None), RecordKeyResponse::Toggle);
+        let short = Some(Duration::from_millis(10));
+        assert_eq!(toggle(HotKeyState::Pressed, short), RecordKeyResponse::Toggle);
+        assert_eq!(toggle(HotKeyState::Released, short), RecordKeyResponse::Ignore);
+    }
+
+    #[test]
+    fn invalid_or_duplicate_keys_are_rejected() {
+        let unknown = [HotkeyBinding::new("alt+Nope", HotkeyAction::Cancel)];
+        assert!(parse_bindings(&unknown).is_err());
+        let twice = [
+            HotkeyBinding::new("alt+Space", HotkeyAction::ToggleRecording),
+            HotkeyBinding::new("option+space", HotkeyAction::RecopyLast),
+        ];
+        let err = parse_bindings(&twice).unwrap_err();
+        assert!(err.to_string().contains("more than once"), "{err}");
+        assert!(parse_bindings(&default_hotkeys()).is_ok());
+    }
+}
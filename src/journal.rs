@@ -0,0 +1,107 @@
+use crate::audio::RecordedAudio;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Extension of recordings still being captured, or left behind by a crash.
+pub const JOURNAL_EXTENSION: &str = "journal";
+const MAGIC: &[u8; 4] = b"DCTJ";
+/// Magic, sample rate (u32) and channel count (u16), little-endian.
+const HEADER_LEN: usize = 10;
+
+/// Appends raw f32 samples to disk while a recording is captured, so a crash
+/// loses at most the last flush interval.
+pub struct JournalWriter {
+    file: BufWriter<File>,
+}
+
+impl JournalWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .with_context(|| format!("create journal {}", path.display()))?;
+        let mut file = BufWriter::new(file);
+        file.write_all(MAGIC)?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&channels.to_le_bytes())?;
+        file.flush().context("write journal header")?;
+        Ok(Self { file })
+    }
+
+    /// Write `samples` and hand them to the OS.
+    pub fn append(&mut self, samples: &[f32]) -> Result<()> {
+        for sample in samples {
+            self.file.write_all(&sample.to_le_bytes())?;
+        }
+        self.file.flush().context("flush journal")
+    }
+}
+
+/// Read a journal back, dropping a partly written trailing frame.
+pub fn read_journal(path: &Path) -> Result<RecordedAudio> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .with_context(|| format!("read journal {}", path.display()))?;
+    if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+        anyhow::bail!("{} is not a recording journal", path.display());
+    }
+    let sample_rate = u32::from_le_bytes(bytes[4..8].try_into()?);
+    let channels = u16::from_le_bytes(bytes[8..10].try_into()?).max(1);
+    let frame_bytes = 4 * channels as usize;
+    let body = &bytes[HEADER_LEN..];
+    let body = &body[..body.len() - body.len() % frame_bytes];
+    let samples = body
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    Ok(RecordedAudio {
+        samples,
+        sample_rate,
+        channels,
+    })
+}
+
+/// Journals in `dir` left over from recordings that never finished, oldest first.
+pub fn unfinished_journals(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut journals = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read dir {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == JOURNAL_EXTENSION) {
+            journals.push(path);
+        }
+    }
+    journals.sort();
+    Ok(journals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn interrupted_journal_reads_back_whole_frames() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("2024-06-01T12-00-00.000+0000.journal");
+        let mut writer = JournalWriter::create(&path, 48_000, 2)?;
+        writer.append(&[0.1, -0.1, 0.2, -0.2])?;
+        writer.append(&[0.3])?;
+        drop(writer);
+        fs::write(dir.path().join("notes.md"), "not a journal")?;
+
+        assert_eq!(unfinished_journals(dir.path())?, vec![path.clone()]);
+        let recorded = read_journal(&path)?;
+        assert_eq!(recorded.sample_rate, 48_000);
+        assert_eq!(recorded.channels, 2);
+        assert_eq!(recorded.samples, vec![0.1, -0.1, 0.2, -0.2]);
+        assert!(JournalWriter::create(&path, 48_000, 2).is_err());
+        Ok(())
+    }
+}
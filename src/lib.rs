@@ -4,11 +4,25 @@ pub mod beep;
 pub mod cli;
 pub mod clipboard;
 pub mod config;
+pub mod decode;
+pub mod encode;
+pub mod filter;
+pub mod flac;
+pub mod hotkeys;
+pub mod journal;
+pub mod live;
 pub mod logging;
 pub mod model;
+#[cfg(feature = "opus")]
+pub mod opus;
+pub mod output;
+pub mod preprocess;
 pub mod queue;
+pub mod remote;
 pub mod storage;
 pub mod transcriber;
+pub mod transcript;
 pub mod tray;
+pub mod vad;
 
 pub use app::run;
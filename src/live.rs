@@ -0,0 +1,221 @@
+use crate::audio::{LiveBuffer, RecordedAudio};
+use crate::decode::{downmix_to_mono, resample_to_16k};
+use crate::transcriber::{continuation_prompt, TranscribeOptions, Transcriber};
+use crate::transcript::{Segment, Transcript};
+use anyhow::Result;
+use crossbeam_channel::{bounded, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Minimum amount of new audio before re-running inference on the window.
+const MIN_NEW_AUDIO_MS: u64 = 1_000;
+/// Once the window is this long, finished segments are committed and dropped from it.
+const COMMIT_AFTER_MS: u64 = 12_000;
+/// Hard cap on the window; whisper only sees 30 s at a time.
+const MAX_WINDOW_MS: u64 = 24_000;
+
+/// Transcribes a recording while it is still being captured. Inference runs on a
+/// rolling window starting at the last committed segment boundary, so by the time
+/// the recording stops only the final few seconds remain.
+pub struct LiveSession {
+    finish_tx: Sender<Arc<RecordedAudio>>,
+    join: thread::JoinHandle<Result<Transcript>>,
+}
+
+impl LiveSession {
+    pub fn start<F>(
+        buffer: LiveBuffer,
+        transcriber: Arc<dyn Transcriber>,
+        options: TranscribeOptions,
+        mut on_partial: F,
+    ) -> Self
+    where
+        F: FnMut(String) + Send + 'static,
+    {
+        let (finish_tx, finish_rx) = bounded::<Arc<RecordedAudio>>(1);
+        let join = thread::spawn(move || {
+            let mut stream =
+                LiveStream::new(transcriber, options, buffer.sample_rate, buffer.channels);
+            loop {
+                match finish_rx.recv_timeout(POLL_INTERVAL) {
+                    Ok(recorded) => return stream.finish(&recorded),
+                    Err(RecvTimeoutError::Disconnected) => return Ok(stream.committed),
+                    Err(RecvTimeoutError::Timeout) => {}
+                }
+                if !stream.should_update(buffer.frames()) {
+                    continue;
+                }
+                let window = buffer.read_from(stream.committed_frame);
+                match stream.update(&window) {
+                    Ok(text) => on_partial(text),
+                    Err(err) => tracing::warn!(error = %err, "live transcription update failed"),
+                }
+            }
+        });
+        Self { finish_tx, join }
+    }
+
+    /// Transcribe whatever is left after the last commit and return the full transcript.
+    pub fn finish(self, recorded: Arc<RecordedAudio>) -> Result<Transcript> {
+        let _ = self.finish_tx.send(recorded);
+        self.join
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("live transcription thread panicked")))
+    }
+}
+
+struct LiveStream {
+    transcriber: Arc<dyn Transcriber>,
+    options: TranscribeOptions,
+    sample_rate: u32,
+    channels: u16,
+    committed: Transcript,
+    committed_frame: usize,
+    last_update_frame: usize,
+}
+
+impl LiveStream {
+    fn new(
+        transcriber: Arc<dyn Transcriber>,
+        options: TranscribeOptions,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Self {
+        Self {
+            transcriber,
+            options,
+            sample_rate,
+            channels: channels.max(1),
+            committed: Transcript::default(),
+            committed_frame: 0,
+            last_update_frame: 0,
+        }
+    }
+
+    fn frames_to_ms(&self, frames: usize) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        frames as u64 * 1000 / self.sample_rate as u64
+    }
+
+    fn ms_to_frames(&self, ms: u64) -> usize {
+        (ms * self.sample_rate as u64 / 1000) as usize
+    }
+
+    fn should_update(&self, captured_frames: usize) -> bool {
+        let new_frames = captured_frames.saturating_sub(self.last_update_frame);
+        self.frames_to_ms(new_frames) >= MIN_NEW_AUDIO_MS
+    }
+
+    /// Re-transcribe the open window, commit settled segments, and return preview text.
+    fn update(&mut self, window: &[f32]) -> Result<String> {
+        let window_frames = window.len() / self.channels as usize;
+        self.last_update_frame = self.committed_frame + window_frames;
+        let window_ms = self.frames_to_ms(window_frames);
+        let transcript = self.transcribe_window(window)?;
+        let mut pending = transcript.segments;
+        if let Some((count, advance_ms)) = commit_point(&pending, window_ms) {
+            let offset_ms = self.frames_to_ms(self.committed_frame);
+            let settled: Vec<Segment> = pending.drain(..count.min(pending.len())).collect();
+            self.commit(settled, offset_ms);
+            self.committed_frame += self.ms_to_frames(advance_ms).min(window_frames);
+        }
+        let mut preview = self.committed.text();
+        for segment in &pending {
+            preview.push_str(&segment.text);
+        }
+        Ok(preview.trim().to_string())
+    }
+
+    fn finish(mut self, recorded: &RecordedAudio) -> Result<Transcript> {
+        let channels = recorded.channels.max(1) as usize;
+        let start = (self.committed_frame * channels).min(recorded.samples.len());
+        let tail = &recorded.samples[start..];
+        if tail.len() >= channels {
+            let offset_ms = self.frames_to_ms(self.committed_frame);
+            let transcript = self.transcribe_window(tail)?;
+            self.commit(transcript.segments, offset_ms);
+        }
+        Ok(self.committed)
+    }
+
+    fn transcribe_window(&self, interleaved: &[f32]) -> Result<Transcript> {
+        let mono = downmix_to_mono(interleaved, self.channels);
+        let samples_16k = resample_to_16k(mono, self.sample_rate)?;
+        let mut options = self.options.clone();
+        options.prompt =
+            continuation_prompt(self.options.prompt.as_deref(), &self.committed.text());
+        self.transcriber.transcribe_samples(samples_16k, &options)
+    }
+
+    fn commit(&mut self, segments: Vec<Segment>, offset_ms: u64) {
+        self.committed
+            .extend_shifted(Transcript::new(segments), offset_ms);
+    }
+}
+
+/// Decide how many leading segments of a window are settled, and how far (in ms
+/// from the window start) the window may advance past them. The last segment
+/// stays open because whisper may still revise it as more audio arrives.
+fn commit_point(segments: &[Segment], window_ms: u64) -> Option<(usize, u64)> {
+    if window_ms < COMMIT_AFTER_MS {
+        return None;
+    }
+    if segments.len() >= 2 {
+        let settled = segments.len() - 1;
+        let advance_ms = segments[settled - 1].end_ms;
+        if advance_ms > 0 {
+            return Some((settled, advance_ms));
+        }
+    }
+    if window_ms >= MAX_WINDOW_MS {
+        let advance_ms = segments
+            .last()
+            .map(|segment| segment.end_ms)
+            .filter(|&end_ms| end_ms > 0)
+            .unwrap_or(window_ms);
+        return Some((segments.len(), advance_ms));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, end_ms: u64) -> Segment {
+        Segment {
+            start_ms,
+            end_ms,
+            text: " words".to_string(),
+            avg_token_prob: 0.9,
+            no_speech_prob: None,
+            speaker: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn commit_point_keeps_last_segment_open() {
+        let segments = [
+            segment(0, 4_000),
+            segment(4_000, 9_000),
+            segment(9_000, 13_000),
+        ];
+        assert_eq!(commit_point(&segments, 8_000), None);
+        assert_eq!(commit_point(&segments, 13_000), Some((2, 9_000)));
+    }
+
+    #[test]
+    fn commit_point_forces_progress_on_long_windows() {
+        assert_eq!(commit_point(&[segment(0, 20_000)], 13_000), None);
+        assert_eq!(
+            commit_point(&[segment(0, 20_000)], 25_000),
+            Some((1, 20_000))
+        );
+        assert_eq!(commit_point(&[], 25_000), Some((0, 25_000)));
+    }
+}
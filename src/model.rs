@@ -105,6 +105,12 @@ pub fn model_info(name: &str) -> Result<ModelInfo> {
     Ok(model_from_spec(spec))
 }
 
+pub fn supports_translation(name: &str) -> bool {
+    model_info(name)
+        .map(|info| matches!(info.languages, LanguageSupport::Multilingual))
+        .unwrap_or(true)
+}
+
 pub fn ensure_model(models_dir: &Path, name: &str) -> Result<PathBuf> {
     ensure_model_with_progress(models_dir, name, |_| {})
 }
@@ -0,0 +1,244 @@
+use crate::audio::RecordedAudio;
+use crate::decode::{downmix_to_mono, resample_to_16k};
+use anyhow::{Context, Result};
+use audiopus::coder::{Decoder as OpusCoderDecoder, Encoder};
+use audiopus::packet::Packet as OpusPacket;
+use audiopus::{Application, Bitrate, MutSignals, SampleRate};
+use ogg::{PacketWriteEndInfo, PacketWriter};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use symphonia::core::audio::SignalSpec;
+use symphonia::core::audio::{AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal};
+use symphonia::core::codecs::{
+    CODEC_TYPE_OPUS, CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult,
+};
+use symphonia::core::errors::{Error as SymphoniaError, decode_error, unsupported_error};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+/// 20 ms frames at 16 kHz.
+const FRAME_SAMPLES: usize = 320;
+const BITRATE: i32 = 24_000;
+/// Upper bound for one encoded frame, from the Opus spec.
+const MAX_PACKET: usize = 1275;
+/// Longest Opus frame (120 ms) at the 48 kHz decoding rate.
+const MAX_DECODED_FRAMES: usize = 5760;
+const STREAM_SERIAL: u32 = 1;
+/// One second of audio per Ogg page, which readers need to work out the trims.
+const PACKETS_PER_PAGE: usize = 50;
+
+/// Write `recorded` as Ogg Opus, downmixed and resampled to the 16 kHz mono that
+/// transcription uses anyway.
+pub fn encode_opus(recorded: &RecordedAudio, output: &Path) -> Result<()> {
+    let mono = downmix_to_mono(&recorded.samples, recorded.channels);
+    let samples = resample_to_16k(mono, recorded.sample_rate)?;
+    let mut encoder = Encoder::new(
+        SampleRate::Hz16000,
+        audiopus::Channels::Mono,
+        Application::Voip,
+    )
+    .context("create Opus encoder")?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(BITRATE))
+        .context("set Opus bitrate")?;
+    let lookahead = encoder.lookahead().context("query Opus lookahead")? as usize;
+    // Ogg Opus counts granule positions and pre-skip at 48 kHz.
+    let pre_skip = lookahead as u64 * 3;
+
+    let file = File::create(output).with_context(|| format!("create {}", output.display()))?;
+    let mut writer = PacketWriter::new(BufWriter::new(file));
+    let header = opus_head(pre_skip as u16);
+    writer.write_packet(header, STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+    writer.write_packet(opus_tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    // The encoder lags its input by `lookahead`; keep feeding silence until the
+    // last real sample has come out.
+    let count = (samples.len() + lookahead).div_ceil(FRAME_SAMPLES).max(1);
+    let mut packet = [0u8; MAX_PACKET];
+    for index in 0..count {
+        let start = (index * FRAME_SAMPLES).min(samples.len());
+        let end = (start + FRAME_SAMPLES).min(samples.len());
+        let mut frame = samples[start..end].to_vec();
+        frame.resize(FRAME_SAMPLES, 0.0);
+        let len = encoder
+            .encode_float(&frame, &mut packet)
+            .context("encode Opus frame")?;
+        // Granule positions count every decoded sample, pre-skip included; the final
+        // one stops short of the last frame's padding.
+        let (info, granule) = if index + 1 == count {
+            (PacketWriteEndInfo::EndStream, pre_skip + samples.len() as u64 * 3)
+        } else if (index + 1) % PACKETS_PER_PAGE == 0 {
+            (PacketWriteEndInfo::EndPage, (index as u64 + 1) * FRAME_SAMPLES as u64 * 3)
+        } else {
+            (PacketWriteEndInfo::NormalPacket, (index as u64 + 1) * FRAME_SAMPLES as u64 * 3)
+        };
+        writer.write_packet(packet[..len].into(), STREAM_SERIAL, info, granule)?;
+    }
+    Ok(())
+}
+
+fn opus_head(pre_skip: u16) -> Box<[u8]> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1);
+    head.push(1);
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&16_000u32.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+    head.into_boxed_slice()
+}
+
+fn opus_tags() -> Box<[u8]> {
+    let vendor = b"dictate";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags.into_boxed_slice()
+}
+
+/// Symphonia demuxes Ogg Opus but has no Opus decoder; this one wraps libopus.
+/// Symphonia leaves the pre-skip to the decoder and marks the end padding as a
+/// gapless trim on the last packet.
+pub struct OpusDecoder {
+    params: CodecParameters,
+    channels: audiopus::Channels,
+    // libopus decoders may move between threads but are not `Sync`.
+    decoder: Mutex<OpusCoderDecoder>,
+    /// Decoder delay still to drop from the start of the stream.
+    skip: usize,
+    pcm: Vec<f32>,
+    buf: AudioBuffer<f32>,
+}
+
+impl Decoder for OpusDecoder {
+    fn try_new(
+        params: &CodecParameters,
+        _options: &DecoderOptions,
+    ) -> symphonia::core::errors::Result<Self> {
+        let (channels, layout) = match params.channels.map(|channels| channels.count()) {
+            Some(1) => (audiopus::Channels::Mono, Channels::FRONT_LEFT),
+            Some(2) => (
+                audiopus::Channels::Stereo,
+                Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+            ),
+            _ => return unsupported_error("opus: only mono and stereo streams are supported"),
+        };
+        let decoder = OpusCoderDecoder::new(SampleRate::Hz48000, channels)
+            .map_err(|_| SymphoniaError::Unsupported("opus: cannot create decoder"))?;
+        Ok(Self {
+            params: params.clone(),
+            channels,
+            decoder: Mutex::new(decoder),
+            skip: params.delay.unwrap_or(0) as usize,
+            pcm: vec![0.0; MAX_DECODED_FRAMES * layout.count()],
+            buf: AudioBuffer::new(MAX_DECODED_FRAMES as u64, SignalSpec::new(48_000, layout)),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        if let Ok(decoder) = OpusCoderDecoder::new(SampleRate::Hz48000, self.channels) {
+            self.decoder = Mutex::new(decoder);
+        }
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> symphonia::core::errors::Result<AudioBufferRef<'_>> {
+        let decoder = self
+            .decoder
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Ok(input) = OpusPacket::try_from(packet.buf()) else {
+            return decode_error("opus: empty packet");
+        };
+        let output = MutSignals::try_from(&mut self.pcm[..])
+            .map_err(|_| SymphoniaError::DecodeError("opus: invalid output buffer"))?;
+        let frames = decoder
+            .decode_float(Some(input), output, false)
+            .map_err(|_| SymphoniaError::DecodeError("opus: invalid packet"))?;
+        let skip = self.skip.min(frames);
+        self.skip -= skip;
+        let start = (skip + packet.trim_start() as usize).min(frames);
+        let end = frames.saturating_sub(packet.trim_end() as usize).max(start);
+        let channels = self.buf.spec().channels.count();
+        self.buf.clear();
+        self.buf.render_reserved(Some(end - start));
+        for (channel, plane) in self.buf.planes_mut().planes().iter_mut().enumerate() {
+            for (frame, sample) in plane.iter_mut().enumerate() {
+                *sample = self.pcm[(start + frame) * channels + channel];
+            }
+        }
+        Ok(self.buf.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buf.as_audio_buffer_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::AudioDecoder;
+    use tempfile::tempdir;
+
+    #[test]
+    fn round_trip_drops_pre_skip_and_final_padding() -> Result<()> {
+        // The last frame partly filled, exactly full, and too full to also hold the
+        // encoder's lookahead; each stream spans several Ogg pages.
+        for tail in [100, 0, 300] {
+            round_trip(16_000 * 3 + tail)?;
+        }
+        Ok(())
+    }
+
+    /// Half a second of silence, then a tone to the end.
+    fn round_trip(len: usize) -> Result<()> {
+        let samples: Vec<f32> = (0..len)
+            .map(|i| match i {
+                0..8_000 => 0.0,
+                _ => 0.25 * (i as f32 * 300.0 * std::f32::consts::TAU / 16_000.0).sin(),
+            })
+            .collect();
+        let recorded = RecordedAudio {
+            samples: samples.clone(),
+            sample_rate: 16_000,
+            channels: 1,
+        };
+        let dir = tempdir()?;
+        let path = dir.path().join("recording.opus");
+        encode_opus(&recorded, &path)?;
+
+        let mut decoder = AudioDecoder::open(&path)?;
+        assert_eq!(decoder.sample_rate(), 48_000);
+        let mut decoded = Vec::new();
+        while let Some(chunk) = decoder.next_chunk()? {
+            decoded.extend(chunk);
+        }
+        assert_eq!(decoded.len(), samples.len() * 3, "{len} samples");
+        // With the pre-skip left in, the tone would start about 6.5 ms late.
+        let onset = decoded
+            .iter()
+            .position(|sample| sample.abs() > 0.05)
+            .context("tone decoded")?;
+        assert!(onset.abs_diff(24_000) < 96, "tone starts at frame {onset}");
+        // Without the lookahead flushed, the last 6.5 ms would be silence.
+        let last_ms = &decoded[decoded.len() - 48..];
+        let peak = last_ms.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak > 0.1, "{len} samples: last millisecond peaks at {peak}");
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use crate::transcript::{Segment, Transcript};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum OutputFormat {
+    Txt,
+    Md,
+    Srt,
+    Vtt,
+    Json,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Txt => "txt",
+            OutputFormat::Md => "md",
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+            OutputFormat::Json => "json",
+            OutputFormat::Tsv => "tsv",
+        }
+    }
+}
+
+pub fn render(transcript: &Transcript, format: OutputFormat) -> Result<String> {
+    let out = match format {
+        OutputFormat::Txt => render_txt(transcript),
+        OutputFormat::Md => render_md(transcript),
+        OutputFormat::Srt => render_srt(transcript),
+        OutputFormat::Vtt => render_vtt(transcript),
+        OutputFormat::Json => render_json(transcript)?,
+        OutputFormat::Tsv => render_tsv(transcript),
+    };
+    Ok(out)
+}
+
+fn render_txt(transcript: &Transcript) -> String {
+    transcript.text()
+}
+
+/// Words below this probability are emphasized in markdown output.
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+fn render_md(transcript: &Transcript) -> String {
+    let mut front_matter = String::new();
+    if let Some(language) = &transcript.language {
+        front_matter = format!(
+            "---\nlanguage: {}\nlanguage_probability: {:.2}\n---\n\n",
+            language.code, language.probability
+        );
+    }
+    if transcript.has_speakers() {
+        let turns: Vec<String> = transcript
+            .turns()
+            .into_iter()
+            .map(|turn| format!("**{}:** {}", turn.speaker, md_words(turn.segments)))
+            .collect();
+        return front_matter + &turns.join("\n\n");
+    }
+    front_matter + &md_words(&transcript.segments)
+}
+
+fn md_words<'a>(segments: impl IntoIterator<Item = &'a Segment>) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for segment in segments {
+        if segment.words.is_empty() {
+            let text = segment.text.trim();
+            if !text.is_empty() {
+                parts.push(text.to_string());
+            }
+            continue;
+        }
+        for word in &segment.words {
+            if word.probability < LOW_CONFIDENCE_THRESHOLD {
+                parts.push(format!("*{}*", word.text));
+            } else {
+                parts.push(word.text.clone());
+            }
+        }
+    }
+    parts.join(" ")
+}
+
+fn render_srt(transcript: &Transcript) -> String {
+    let mut out = String::new();
+    for (idx, segment) in cues(transcript).enumerate() {
+        let _ = writeln!(
+            out,
+            "{}\n{} --> {}\n{}\n",
+            idx + 1,
+            format_timestamp(segment.start_ms, ','),
+            format_timestamp(segment.end_ms, ','),
+            cue_text(segment)
+        );
+    }
+    out
+}
+
+fn render_vtt(transcript: &Transcript) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for segment in cues(transcript) {
+        let _ = writeln!(
+            out,
+            "{} --> {}\n{}\n",
+            format_timestamp(segment.start_ms, '.'),
+            format_timestamp(segment.end_ms, '.'),
+            match &segment.speaker {
+                Some(speaker) => format!("<v {speaker}>{}", segment.text.trim()),
+                None => segment.text.trim().to_string(),
+            }
+        );
+    }
+    out
+}
+
+fn render_json(transcript: &Transcript) -> Result<String> {
+    let mut out = serde_json::to_string_pretty(transcript).context("serialize transcript")?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn render_tsv(transcript: &Transcript) -> String {
+    let speakers = transcript.has_speakers();
+    let mut out = String::from(if speakers {
+        "start\tend\tspeaker\ttext\n"
+    } else {
+        "start\tend\ttext\n"
+    });
+    for segment in cues(transcript) {
+        let text: String = segment
+            .text
+            .trim()
+            .chars()
+            .map(|ch| if ch == '\t' || ch == '\n' || ch == '\r' { ' ' } else { ch })
+            .collect();
+        if speakers {
+            let speaker = segment.speaker.as_deref().unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                segment.start_ms, segment.end_ms, speaker, text
+            );
+        } else {
+            let _ = writeln!(out, "{}\t{}\t{}", segment.start_ms, segment.end_ms, text);
+        }
+    }
+    out
+}
+
+fn cue_text(segment: &Segment) -> String {
+    match &segment.speaker {
+        Some(speaker) => format!("{speaker}: {}", segment.text.trim()),
+        None => segment.text.trim().to_string(),
+    }
+}
+
+// Subtitle players choke on empty cues, so skip segments without text.
+fn cues(transcript: &Transcript) -> impl Iterator<Item = &Segment> {
+    transcript
+        .segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+}
+
+fn format_timestamp(ms: u64, millis_separator: char) -> String {
+    let hours = ms / 3_600_000;
+    let minutes = (ms / 60_000) % 60;
+    let seconds = (ms / 1_000) % 60;
+    let millis = ms % 1_000;
+    format!("{hours:02}:{minutes:02}:{seconds:02}{millis_separator}{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{DetectedLanguage, Word};
+
+    fn sample() -> Transcript {
+        Transcript::new(vec![
+            Segment {
+                start_ms: 0,
+                end_ms: 1_500,
+                text: " Hello there.".to_string(),
+                avg_token_prob: 0.9,
+                no_speech_prob: None,
+                speaker: None,
+                words: Vec::new(),
+            },
+            Segment {
+                start_ms: 3_661_250,
+                end_ms: 3_663_000,
+                text: " Tabs\tand more.".to_string(),
+                avg_token_prob: 0.8,
+                no_speech_prob: None,
+                speaker: None,
+                words: Vec::new(),
+            },
+        ])
+    }
+
+    #[test]
+    fn srt_numbers_cues_and_uses_comma_millis() -> Result<()> {
+        let out = render(&sample(), OutputFormat::Srt)?;
+        assert_eq!(
+            out,
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n01:01:01,250 --> 01:01:03,000\nTabs\tand more.\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn vtt_has_header_and_dot_millis() -> Result<()> {
+        let out = render(&sample(), OutputFormat::Vtt)?;
+        assert!(out.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello there.\n"));
+        assert!(out.contains("01:01:01.250 --> 01:01:03.000\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn tsv_escapes_tabs_in_text() -> Result<()> {
+        let out = render(&sample(), OutputFormat::Tsv)?;
+        assert_eq!(
+            out,
+            "start\tend\ttext\n0\t1500\tHello there.\n3661250\t3663000\tTabs and more.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn md_emphasizes_low_confidence_words() -> Result<()> {
+        let mut transcript = sample();
+        transcript.segments[0].words = vec![
+            Word {
+                start_ms: 0,
+                end_ms: 600,
+                text: "Hello".to_string(),
+                probability: 0.95,
+            },
+            Word {
+                start_ms: 600,
+                end_ms: 1_500,
+                text: "there.".to_string(),
+                probability: 0.2,
+            },
+        ];
+        let out = render(&transcript, OutputFormat::Md)?;
+        assert_eq!(out, "Hello *there.* Tabs\tand more.");
+        Ok(())
+    }
+
+    #[test]
+    fn md_front_matter_reports_detected_language() -> Result<()> {
+        let mut transcript = sample();
+        transcript.language = Some(DetectedLanguage {
+            code: "ru".to_string(),
+            probability: 0.934,
+        });
+        let out = render(&transcript, OutputFormat::Md)?;
+        assert!(
+            out.starts_with("---\nlanguage: ru\nlanguage_probability: 0.93\n---\n\nHello there.")
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&transcript, OutputFormat::Json)?)?;
+        assert_eq!(json["language"]["code"], "ru");
+        Ok(())
+    }
+
+    #[test]
+    fn speaker_labels_appear_in_every_format() -> Result<()> {
+        let mut transcript = sample();
+        transcript.segments[0].speaker = Some("Speaker 1".to_string());
+        transcript.segments[1].speaker = Some("Speaker 2".to_string());
+        assert!(render(&transcript, OutputFormat::Srt)?.contains("\nSpeaker 1: Hello there.\n"));
+        assert!(
+            render(&transcript, OutputFormat::Vtt)?.contains("\n<v Speaker 2>Tabs\tand more.\n")
+        );
+        assert!(render(&transcript, OutputFormat::Tsv)?
+            .starts_with("start\tend\tspeaker\ttext\n0\t1500\tSpeaker 1\tHello there.\n"));
+        assert_eq!(
+            render(&transcript, OutputFormat::Md)?,
+            "**Speaker 1:** Hello there.\n\n**Speaker 2:** Tabs\tand more."
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&transcript, OutputFormat::Json)?)?;
+        assert_eq!(json["segments"][1]["speaker"], "Speaker 2");
+        Ok(())
+    }
+
+    #[test]
+    fn json_includes_segments() -> Result<()> {
+        let out = render(&sample(), OutputFormat::Json)?;
+        let value: serde_json::Value = serde_json::from_str(&out)?;
+        assert_eq!(value["segments"][1]["start_ms"], 3_661_250);
+        assert_eq!(value["segments"][0]["text"], " Hello there.");
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+
+/// Frame length used for gating and level measurement.
+const FRAME_MS: usize = 20;
+/// Gain applied to frames the noise gate closes on (-20 dB).
+const GATE_ATTENUATION: f32 = 0.1;
+/// Ceiling for the normalized signal, just under full scale.
+const PEAK_LIMIT: f32 = 0.99;
+
+/// Signal conditioning applied to 16 kHz audio before voice activity detection,
+/// so quiet or noisy recordings reach whisper at a usable level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessConfig {
+    /// Off by default; the other settings apply once it is turned on.
+    pub enabled: bool,
+    /// Subtract the mean so a biased mic does not skew levels.
+    pub remove_dc: bool,
+    /// High-pass cutoff for rumble and handling noise; 0 disables the filter.
+    pub highpass_hz: f32,
+    /// Frames this many dB below the loud parts of the recording are attenuated;
+    /// 0 disables the gate.
+    pub gate_db: f32,
+    /// RMS level speech is normalized to, in dBFS.
+    pub target_dbfs: f32,
+    /// Largest boost normalization may apply.
+    pub max_gain_db: f32,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            remove_dc: true,
+            highpass_hz: 80.0,
+            gate_db: 40.0,
+            target_dbfs: -20.0,
+            max_gain_db: 30.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessStats {
+    pub dc_offset: f32,
+    pub gated_frames: usize,
+    pub gain_db: f32,
+}
+
+/// Run the enabled stages in order: DC removal, high-pass, noise gate, loudness
+/// normalization. Returns `None` when preprocessing is off or there is no audio.
+pub fn preprocess(
+    samples: &mut [f32],
+    sample_rate: u32,
+    config: &PreprocessConfig,
+) -> Option<PreprocessStats> {
+    if !config.enabled || samples.is_empty() || sample_rate == 0 {
+        return None;
+    }
+    let dc_offset = if config.remove_dc {
+        remove_dc(samples)
+    } else {
+        0.0
+    };
+    if config.highpass_hz > 0.0 && config.highpass_hz < sample_rate as f32 * 0.45 {
+        high_pass(samples, sample_rate, config.highpass_hz);
+    }
+    let frame_len = (sample_rate as usize * FRAME_MS / 1000).max(1);
+    let levels: Vec<f32> = samples.chunks(frame_len).map(rms).collect();
+    let reference = percentile(&levels, 0.95);
+    let gate = if config.gate_db > 0.0 {
+        reference * db_to_gain(-config.gate_db)
+    } else {
+        0.0
+    };
+    let gated_frames = noise_gate(samples, frame_len, &levels, gate);
+
+    let active: Vec<f32> = levels.iter().copied().filter(|&l| l > gate).collect();
+    let active_rms =
+        (active.iter().map(|l| l * l).sum::<f32>() / active.len().max(1) as f32).sqrt();
+    let mut gain_db = 0.0;
+    if active_rms > 0.0 {
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let gain = (db_to_gain(config.target_dbfs) / active_rms)
+            .min(db_to_gain(config.max_gain_db))
+            .min(PEAK_LIMIT / peak.max(f32::EPSILON));
+        for sample in samples.iter_mut() {
+            *sample *= gain;
+        }
+        gain_db = 20.0 * gain.log10();
+    }
+    Some(PreprocessStats {
+        dc_offset,
+        gated_frames,
+        gain_db,
+    })
+}
+
+fn remove_dc(samples: &mut [f32]) -> f32 {
+    let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64;
+    let mean = mean as f32;
+    for sample in samples.iter_mut() {
+        *sample -= mean;
+    }
+    mean
+}
+
+/// Second-order Butterworth high-pass (RBJ biquad), in place.
+fn high_pass(samples: &mut [f32], sample_rate: u32, cutoff_hz: f32) {
+    let w0 = std::f32::consts::TAU * cutoff_hz / sample_rate as f32;
+    let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+    let cos = w0.cos();
+    let a0 = 1.0 + alpha;
+    let b0 = (1.0 + cos) / 2.0 / a0;
+    let b1 = -(1.0 + cos) / a0;
+    let a1 = -2.0 * cos / a0;
+    let a2 = (1.0 - alpha) / a0;
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for sample in samples.iter_mut() {
+        let x = *sample;
+        let y = b0 * x + b1 * x1 + b0 * x2 - a1 * y1 - a2 * y2;
+        x2 = x1;
+        x1 = x;
+        y2 = y1;
+        y1 = y;
+        *sample = y;
+    }
+}
+
+/// Attenuate frames below `threshold`, ramping the gain across each frame so the
+/// gate does not click. Returns the number of closed frames.
+fn noise_gate(samples: &mut [f32], frame_len: usize, levels: &[f32], threshold: f32) -> usize {
+    if threshold <= 0.0 {
+        return 0;
+    }
+    let mut gated = 0;
+    let mut previous_gain = 1.0f32;
+    for (frame, &level) in samples.chunks_mut(frame_len).zip(levels) {
+        let gain = if level < threshold {
+            gated += 1;
+            GATE_ATTENUATION
+        } else {
+            1.0
+        };
+        let step = (gain - previous_gain) / frame.len() as f32;
+        for (idx, sample) in frame.iter_mut().enumerate() {
+            *sample *= previous_gain + step * (idx + 1) as f32;
+        }
+        previous_gain = gain;
+    }
+    gated
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+fn percentile(values: &[f32], fraction: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let idx = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+    sorted[idx]
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize, hz: f32, amp: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amp * (i as f32 * hz * std::f32::consts::TAU / 16_000.0).sin())
+            .collect()
+    }
+
+    fn enabled() -> PreprocessConfig {
+        PreprocessConfig {
+            enabled: true,
+            ..PreprocessConfig::default()
+        }
+    }
+
+    #[test]
+    fn quiet_biased_speech_is_centered_and_raised_to_target() {
+        let mut samples: Vec<f32> = tone(32_000, 300.0, 0.01)
+            .into_iter()
+            .map(|s| s + 0.05)
+            .collect();
+        let stats = preprocess(&mut samples, 16_000, &enabled()).expect("preprocessing ran");
+        assert!((stats.dc_offset - 0.05).abs() < 1e-3);
+        assert!(stats.gain_db > 20.0);
+        let tail = &samples[8_000..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        assert!(mean.abs() < 1e-3);
+        let level = 20.0 * rms(tail).log10();
+        assert!((level - -20.0).abs() < 1.0, "level {level} dBFS");
+    }
+
+    #[test]
+    fn gate_closes_on_background_hiss_between_speech() {
+        let mut samples = tone(16_000, 300.0, 0.3);
+        samples.extend(tone(16_000, 5_000.0, 0.0005));
+        samples.extend(tone(16_000, 300.0, 0.3));
+        let config = PreprocessConfig {
+            target_dbfs: -10.0,
+            ..enabled()
+        };
+        let stats = preprocess(&mut samples, 16_000, &config).expect("preprocessing ran");
+        assert!(stats.gated_frames >= 45);
+        let speech = rms(&samples[4_000..12_000]);
+        let hiss = rms(&samples[20_000..28_000]);
+        assert!(20.0 * (speech / hiss).log10() > 70.0);
+
+        let mut untouched = tone(1_600, 300.0, 0.3);
+        let disabled = PreprocessConfig::default();
+        assert!(!disabled.enabled);
+        assert!(preprocess(&mut untouched, 16_000, &disabled).is_none());
+        assert_eq!(untouched, tone(1_600, 300.0, 0.3));
+    }
+}
@@ -1,16 +1,41 @@
+use crate::audio::RecordedAudio;
+use crate::hotkeys::RecordProfile;
+use crate::vad::VadConfig;
 use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JobKind {
     Hotkey,
+    /// Second pass over a hotkey recording with the larger model.
+    Refine,
     Auto,
 }
 
 #[derive(Debug, Clone)]
 pub struct HotkeyJob {
+    /// Where the recording is being saved; it may not be written yet.
     pub audio_path: PathBuf,
     pub text_path: PathBuf,
+    /// The captured samples, transcribed directly rather than from `audio_path`.
+    pub audio: Arc<RecordedAudio>,
+    /// Overrides from the `record` hotkey that started the recording.
+    pub profile: RecordProfile,
+}
+
+/// Background transcription of a saved hotkey recording: the second pass after a
+/// draft, or a recording recovered from its journal.
+#[derive(Debug, Clone)]
+pub struct RefineJob {
+    pub audio_path: PathBuf,
+    pub text_path: PathBuf,
+    /// Draft text copied to the clipboard by the first pass, if there was one.
+    pub draft: Option<String>,
+    /// Samples still in memory from the first pass; recovered recordings are read
+    /// from `audio_path`.
+    pub audio: Option<Arc<RecordedAudio>>,
+    pub profile: RecordProfile,
 }
 
 #[derive(Debug, Clone)]
@@ -18,11 +43,17 @@ pub struct AutoJob {
     pub input_path: PathBuf,
     pub output_path: PathBuf,
     pub processed_path: PathBuf,
+    /// Language code, or `auto` to detect it.
+    pub language: String,
+    pub translate: bool,
+    pub vad: VadConfig,
+    pub split_channels: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum Job {
     Hotkey(HotkeyJob),
+    Refine(RefineJob),
     Auto(AutoJob),
 }
 
@@ -30,8 +61,12 @@ pub enum Job {
 pub struct JobQueue {
     hotkey_session_active: bool,
     pending_hotkey: Option<HotkeyJob>,
+    refine_queue: VecDeque<RefineJob>,
     auto_queue: VecDeque<AutoJob>,
     active: Option<JobKind>,
+    /// The running refine or auto job, kept so a preempted job can be put back.
+    active_background: Option<Job>,
+    background_preempted: bool,
 }
 
 impl JobQueue {
@@ -39,8 +74,11 @@ impl JobQueue {
         Self {
             hotkey_session_active: false,
             pending_hotkey: None,
+            refine_queue: VecDeque::new(),
             auto_queue: VecDeque::new(),
             active: None,
+            active_background: None,
+            background_preempted: false,
         }
     }
 
@@ -72,6 +110,10 @@ impl JobQueue {
         true
     }
 
+    pub fn enqueue_refine(&mut self, job: RefineJob) {
+        self.refine_queue.push_back(job);
+    }
+
     pub fn enqueue_auto(&mut self, job: AutoJob) {
         self.auto_queue.push_back(job);
     }
@@ -87,11 +129,16 @@ impl JobQueue {
         if self.hotkey_session_active {
             return None;
         }
-        if let Some(job) = self.auto_queue.pop_front() {
-            self.active = Some(JobKind::Auto);
-            return Some(Job::Auto(job));
-        }
-        None
+        let (kind, job) = if let Some(job) = self.refine_queue.pop_front() {
+            (JobKind::Refine, Job::Refine(job))
+        } else if let Some(job) = self.auto_queue.pop_front() {
+            (JobKind::Auto, Job::Auto(job))
+        } else {
+            return None;
+        };
+        self.active = Some(kind);
+        self.active_background = Some(job.clone());
+        Some(job)
     }
 
     pub fn active_kind(&self) -> Option<JobKind> {
@@ -102,10 +149,44 @@ impl JobQueue {
         self.auto_queue.len()
     }
 
+    /// True once per running refine or auto job when a hotkey job is waiting behind
+    /// it; the caller aborts the job and reports it through `abort_active_background`.
+    pub fn preempt_background(&mut self) -> bool {
+        if !matches!(self.active, Some(JobKind::Refine | JobKind::Auto))
+            || self.pending_hotkey.is_none()
+            || self.background_preempted
+        {
+            return false;
+        }
+        self.background_preempted = true;
+        true
+    }
+
+    /// End an aborted refine or auto job. A preempted job goes back to the front of
+    /// its queue and `true` is returned; a cancelled one is dropped.
+    pub fn abort_active_background(&mut self) -> bool {
+        if !matches!(self.active, Some(JobKind::Refine | JobKind::Auto)) {
+            return false;
+        }
+        let requeued = self.background_preempted;
+        match self.active_background.take() {
+            Some(Job::Refine(job)) if requeued => self.refine_queue.push_front(job),
+            Some(Job::Auto(job)) if requeued => self.auto_queue.push_front(job),
+            _ => {}
+        }
+        self.active = None;
+        self.background_preempted = false;
+        requeued
+    }
+
     pub fn complete_active(&mut self, kind: JobKind) {
         if self.active == Some(kind) {
             self.active = None;
         }
+        if matches!(kind, JobKind::Refine | JobKind::Auto) {
+            self.active_background = None;
+            self.background_preempted = false;
+        }
         if kind == JobKind::Hotkey {
             self.hotkey_session_active = false;
         }
@@ -122,23 +203,39 @@ impl Default for JobQueue {
 mod tests {
     use super::*;
 
+    fn hotkey_job() -> HotkeyJob {
+        HotkeyJob {
+            audio_path: PathBuf::from("rec.m4a"),
+            text_path: PathBuf::from("rec.md"),
+            audio: Arc::new(RecordedAudio {
+                samples: Vec::new(),
+                sample_rate: 16_000,
+                channels: 1,
+            }),
+            profile: RecordProfile::default(),
+        }
+    }
+
+    fn auto_job(name: &str) -> AutoJob {
+        AutoJob {
+            input_path: PathBuf::from(name),
+            output_path: PathBuf::from("out.md"),
+            processed_path: PathBuf::from("processed.m4a"),
+            language: "en".to_string(),
+            translate: false,
+            vad: VadConfig::default(),
+            split_channels: false,
+        }
+    }
+
     #[test]
     fn hotkey_blocks_auto_until_complete() {
         let mut queue = JobQueue::new();
-        let auto_job = AutoJob {
-            input_path: PathBuf::from("in.m4a"),
-            output_path: PathBuf::from("out.md"),
-            processed_path: PathBuf::from("processed.m4a"),
-        };
-        queue.enqueue_auto(auto_job);
+        queue.enqueue_auto(auto_job("in.m4a"));
         assert!(queue.begin_hotkey_session());
         assert!(queue.next_job().is_none());
 
-        let hotkey_job = HotkeyJob {
-            audio_path: PathBuf::from("rec.m4a"),
-            text_path: PathBuf::from("rec.md"),
-        };
-        assert!(queue.enqueue_hotkey(hotkey_job));
+        assert!(queue.enqueue_hotkey(hotkey_job()));
         assert!(matches!(queue.next_job(), Some(Job::Hotkey(_))));
         queue.complete_active(JobKind::Hotkey);
         assert!(matches!(queue.next_job(), Some(Job::Auto(_))));
@@ -147,17 +244,89 @@ mod tests {
     #[test]
     fn hotkey_priority_over_auto() {
         let mut queue = JobQueue::new();
-        queue.enqueue_auto(AutoJob {
-            input_path: PathBuf::from("in.m4a"),
-            output_path: PathBuf::from("out.md"),
-            processed_path: PathBuf::from("processed.m4a"),
-        });
+        queue.enqueue_auto(auto_job("in.m4a"));
+        assert!(queue.begin_hotkey_session());
+        assert!(queue.enqueue_hotkey(hotkey_job()));
+        assert!(matches!(queue.next_job(), Some(Job::Hotkey(_))));
+    }
+
+    #[test]
+    fn preempted_auto_job_resumes_first_after_hotkey() {
+        let mut queue = JobQueue::new();
+        for name in ["long.m4a", "next.m4a"] {
+            queue.enqueue_auto(auto_job(name));
+        }
+        assert!(matches!(queue.next_job(), Some(Job::Auto(_))));
+        assert!(!queue.preempt_background());
+
+        assert!(queue.begin_hotkey_session());
+        assert!(queue.enqueue_hotkey(hotkey_job()));
+        assert!(queue.preempt_background());
+        assert!(!queue.preempt_background());
+        assert!(queue.next_job().is_none());
+        assert!(queue.abort_active_background());
+
+        assert!(matches!(queue.next_job(), Some(Job::Hotkey(_))));
+        queue.complete_active(JobKind::Hotkey);
+        match queue.next_job() {
+            Some(Job::Auto(job)) => assert_eq!(job.input_path, PathBuf::from("long.m4a")),
+            other => panic!("expected the preempted job, got {other:?}"),
+        }
+        assert_eq!(queue.auto_queue_len(), 1);
+        assert!(!queue.abort_active_background());
+        assert_eq!(queue.auto_queue_len(), 1);
+    }
+
+    #[test]
+    fn refinement_runs_before_queued_auto_jobs() {
+        let mut queue = JobQueue::new();
+        queue.enqueue_auto(auto_job("in.m4a"));
         assert!(queue.begin_hotkey_session());
-        assert!(queue.enqueue_hotkey(HotkeyJob {
+        assert!(queue.enqueue_hotkey(hotkey_job()));
+        assert!(matches!(queue.next_job(), Some(Job::Hotkey(_))));
+        queue.enqueue_refine(RefineJob {
             audio_path: PathBuf::from("rec.m4a"),
             text_path: PathBuf::from("rec.md"),
-        }));
+            draft: Some("draft".to_string()),
+            audio: None,
+            profile: RecordProfile::default(),
+        });
+        queue.complete_active(JobKind::Hotkey);
+        assert!(matches!(queue.next_job(), Some(Job::Refine(_))));
+        assert!(queue.next_job().is_none());
+        queue.complete_active(JobKind::Refine);
+        assert!(matches!(queue.next_job(), Some(Job::Auto(_))));
+    }
+
+    #[test]
+    fn preempted_refine_resumes_after_hotkey() {
+        let mut queue = JobQueue::new();
+        queue.enqueue_refine(RefineJob {
+            audio_path: PathBuf::from("first.m4a"),
+            text_path: PathBuf::from("first.md"),
+            draft: Some("draft".to_string()),
+            audio: None,
+            profile: RecordProfile::default(),
+        });
+        assert!(matches!(queue.next_job(), Some(Job::Refine(_))));
+        assert!(!queue.preempt_background());
+
+        assert!(queue.begin_hotkey_session());
+        assert!(queue.enqueue_hotkey(hotkey_job()));
+        assert!(queue.preempt_background());
+        assert!(!queue.preempt_background());
+        assert!(queue.next_job().is_none());
+        assert!(queue.abort_active_background());
+
         assert!(matches!(queue.next_job(), Some(Job::Hotkey(_))));
+        queue.complete_active(JobKind::Hotkey);
+        match queue.next_job() {
+            Some(Job::Refine(job)) => assert_eq!(job.audio_path, PathBuf::from("first.m4a")),
+            other => panic!("expected the preempted refine, got {other:?}"),
+        }
+        // A cancelled refine is dropped rather than queued again.
+        assert!(!queue.abort_active_background());
+        assert!(queue.next_job().is_none());
     }
 
     #[test]
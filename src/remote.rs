@@ -0,0 +1,554 @@
+use crate::audio::RecordedAudio;
+use crate::decode::{downmix_to_mono, resample_to_16k, AudioDecoder, StreamingResampler};
+use crate::transcriber::{Progress, TranscribeOptions, Transcriber};
+use crate::transcript::{DetectedLanguage, Segment, Transcript, Word};
+use anyhow::{Context, Result};
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Where to send audio when transcription runs on a server instead of locally.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    /// Server root, e.g. `http://gpu-box:8000`; `/v1/audio/...` is appended.
+    pub base_url: String,
+    /// Model name sent with every request.
+    pub model: String,
+    /// Bearer token; falls back to the `OPENAI_API_KEY` environment variable.
+    pub api_key: Option<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:8000".to_string(),
+            model: "whisper-1".to_string(),
+            api_key: None,
+            timeout_secs: 600,
+        }
+    }
+}
+
+/// Backend that posts audio to an OpenAI-compatible `/v1/audio/transcriptions`
+/// endpoint and reads back a `verbose_json` transcript.
+pub struct RemoteTranscriber {
+    config: RemoteConfig,
+    api_key: Option<String>,
+    client: Client,
+}
+
+impl RemoteTranscriber {
+    pub fn new(config: RemoteConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("build http client")?;
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+            .filter(|key| !key.trim().is_empty());
+        Ok(Self {
+            config,
+            api_key,
+            client,
+        })
+    }
+
+    fn endpoint(&self, translate: bool) -> String {
+        let action = if translate {
+            "translations"
+        } else {
+            "transcriptions"
+        };
+        format!(
+            "{}/v1/audio/{action}",
+            self.config.base_url.trim_end_matches('/')
+        )
+    }
+
+    fn post(
+        &self,
+        file_name: String,
+        audio: Vec<u8>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        options.cancel.check()?;
+        let url = self.endpoint(options.translate);
+        let mut form = Form::new()
+            .part("file", Part::bytes(audio).file_name(file_name))
+            .text("model", self.config.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment")
+            .text("timestamp_granularities[]", "word");
+        if !options.translate && !options.detects_language() {
+            // Match the local backend, which transcribes English unless told otherwise.
+            let language = options
+                .language
+                .as_deref()
+                .map(str::trim)
+                .filter(|language| !language.is_empty())
+                .unwrap_or("en");
+            form = form.text("language", language.to_string());
+        }
+        if let Some(prompt) = options.prompt.clone() {
+            form = form.text("prompt", prompt);
+        }
+        let mut request = self.client.post(&url).multipart(form);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        tracing::debug!(url = %url, model = %self.config.model, "posting audio");
+        let response = request.send().with_context(|| format!("POST {url}"))?;
+        // The request cannot be interrupted; a late answer is simply discarded.
+        options.cancel.check()?;
+        let status = response.status();
+        let body = response.text().context("read transcription response")?;
+        if !status.is_success() {
+            anyhow::bail!("transcription server returned {status}: {}", body.trim());
+        }
+        let mut transcript = parse_verbose_json(&body)?;
+        if options.detects_language() && !options.languages.is_empty() {
+            // The API takes no allow-list, so check the server's pick afterwards.
+            let allowed = options.languages.join(", ");
+            let Some(language) = &transcript.language else {
+                anyhow::bail!(
+                    "transcription server did not report a language; \
+                     cannot restrict detection to `languages` ({allowed})"
+                );
+            };
+            if !options
+                .languages
+                .iter()
+                .any(|allowed| allowed.trim().eq_ignore_ascii_case(&language.code))
+            {
+                anyhow::bail!(
+                    "transcription server detected `{}`, which is not in `languages` \
+                     ({allowed}); set `language` to transcribe it anyway",
+                    language.code
+                );
+            }
+        }
+        options.filter.apply(&mut transcript);
+        Ok(transcript)
+    }
+
+    fn transcribe_channels(
+        &self,
+        path: &Path,
+        channels: usize,
+        progress: &mut Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        let mut transcripts = Vec::with_capacity(channels);
+        for channel in 0..channels {
+            options.cancel.check()?;
+            let mut decoder = AudioDecoder::open(path)?;
+            decoder.select_channel(channel);
+            let mut resampler = StreamingResampler::new(decoder.sample_rate())?;
+            let mut samples = Vec::new();
+            while let Some(chunk) = decoder.next_chunk()? {
+                samples.extend(resampler.process(&chunk)?);
+            }
+            samples.extend(resampler.flush()?);
+            let transcript = self
+                .transcribe_samples(samples, options)
+                .with_context(|| format!("transcribe channel {}", channel + 1))?;
+            transcripts.push(transcript);
+            if let Some(progress) = progress.as_mut() {
+                progress(((channel + 1) * 100 / channels) as i32);
+            }
+        }
+        Ok(Transcript::merge_speakers(transcripts))
+    }
+}
+
+impl Transcriber for RemoteTranscriber {
+    fn transcribe(
+        &self,
+        path: &Path,
+        mut progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        if let Some(progress) = progress.as_mut() {
+            progress(0);
+        }
+        if options.split_channels {
+            let channels = AudioDecoder::open(path)?.channels().unwrap_or(1);
+            if channels >= 2 {
+                return self.transcribe_channels(path, channels, &mut progress, options);
+            }
+        }
+        let audio = fs::read(path).with_context(|| format!("read audio {}", path.display()))?;
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("audio")
+            .to_string();
+        let transcript = self.post(file_name, audio, options)?;
+        if let Some(progress) = progress.as_mut() {
+            progress(100);
+        }
+        Ok(transcript)
+    }
+
+    fn transcribe_samples(
+        &self,
+        samples_16k: Vec<f32>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        self.post("audio.wav".to_string(), wav_16k(&samples_16k), options)
+    }
+
+    fn transcribe_recording(
+        &self,
+        recorded: &RecordedAudio,
+        mut progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        if let Some(progress) = progress.as_mut() {
+            progress(0);
+        }
+        let mono = downmix_to_mono(&recorded.samples, recorded.channels);
+        let transcript =
+            self.transcribe_samples(resample_to_16k(mono, recorded.sample_rate)?, options)?;
+        if let Some(progress) = progress.as_mut() {
+            progress(100);
+        }
+        Ok(transcript)
+    }
+}
+
+#[derive(Deserialize)]
+struct VerboseResponse {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<ResponseSegment>,
+    #[serde(default)]
+    words: Vec<ResponseWord>,
+}
+
+#[derive(Deserialize)]
+struct ResponseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f32>,
+    #[serde(default)]
+    no_speech_prob: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct ResponseWord {
+    word: String,
+    start: f64,
+    end: f64,
+    /// Not part of the OpenAI schema, but faster-whisper based servers send it.
+    #[serde(default)]
+    probability: Option<f32>,
+}
+
+fn parse_verbose_json(body: &str) -> Result<Transcript> {
+    let response: VerboseResponse =
+        serde_json::from_str(body).context("parse transcription response")?;
+    let language = response.language.as_deref().and_then(detected_language);
+    let mut segments: Vec<Segment> = response
+        .segments
+        .into_iter()
+        .map(|segment| Segment {
+            start_ms: seconds_to_ms(segment.start),
+            end_ms: seconds_to_ms(segment.end),
+            text: segment.text,
+            avg_token_prob: segment.avg_logprob.map(f32::exp).unwrap_or(1.0),
+            no_speech_prob: segment.no_speech_prob,
+            speaker: None,
+            words: Vec::new(),
+        })
+        .collect();
+    if segments.is_empty() && !response.text.trim().is_empty() {
+        segments.push(Segment {
+            start_ms: 0,
+            end_ms: response
+                .words
+                .last()
+                .map_or(0, |word| seconds_to_ms(word.end)),
+            text: response.text,
+            avg_token_prob: 1.0,
+            no_speech_prob: None,
+            speaker: None,
+            words: Vec::new(),
+        });
+    }
+    for word in response.words {
+        let word = Word {
+            start_ms: seconds_to_ms(word.start),
+            end_ms: seconds_to_ms(word.end),
+            text: word.word.trim().to_string(),
+            probability: word.probability.unwrap_or(1.0),
+        };
+        // Words come as one flat list; hand each to the segment it starts in.
+        let owner = segments
+            .iter()
+            .rposition(|segment| segment.start_ms <= word.start_ms)
+            .unwrap_or(0);
+        if let Some(segment) = segments.get_mut(owner) {
+            segment.words.push(word);
+        }
+    }
+    let mut transcript = Transcript::new(segments);
+    transcript.language = language;
+    Ok(transcript)
+}
+
+/// Servers report either a whisper code (`en`) or, like OpenAI, the language name
+/// (`english`); whisper knows both. No probability is reported, so it is taken as 1.
+fn detected_language(reported: &str) -> Option<DetectedLanguage> {
+    let name = reported.trim().to_lowercase();
+    let code = (!name.is_empty() && !name.contains('\0'))
+        .then(|| whisper_rs::get_lang_id(&name))
+        .flatten()
+        .and_then(whisper_rs::get_lang_str);
+    if code.is_none() {
+        tracing::warn!(language = %reported, "server reported an unknown language");
+    }
+    code.map(|code| DetectedLanguage {
+        code: code.to_string(),
+        probability: 1.0,
+    })
+}
+
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+/// 16-bit PCM WAV of 16 kHz mono samples.
+fn wav_16k(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&16_000u32.to_le_bytes());
+    out.extend_from_slice(&32_000u32.to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcriber::Cancelled;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use tempfile::tempdir;
+
+    /// Accept one request, answer it with `status` and `body`, and hand back the
+    /// request line and body the client sent.
+    fn stand_in_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in server");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        let join = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("accept");
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).expect("request line");
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).expect("header");
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().expect("content length");
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).expect("body");
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .expect("write response");
+            (
+                request_line.trim().to_string(),
+                String::from_utf8_lossy(&request_body).into_owned(),
+            )
+        });
+        (base_url, join)
+    }
+
+    #[test]
+    fn posts_audio_and_parses_verbose_json() -> Result<()> {
+        let (base_url, server) = stand_in_server(
+            "200 OK",
+            r#"{"task":"transcribe","language":"english","duration":2.4,"text":" Hello there. General Kenobi.",
+                "segments":[{"id":0,"start":0.0,"end":1.2,"text":" Hello there.","avg_logprob":-0.1,"no_speech_prob":0.02},
+                            {"id":1,"start":1.2,"end":2.4,"text":" General Kenobi.","avg_logprob":-0.3,"no_speech_prob":0.01}],
+                "words":[{"word":"Hello","start":0.0,"end":0.5},{"word":"there.","start":0.5,"end":1.2},
+                         {"word":"General","start":1.3,"end":1.8},{"word":"Kenobi.","start":1.8,"end":2.4}]}"#,
+        );
+        let dir = tempdir()?;
+        let audio = dir.path().join("call.m4a");
+        fs::write(&audio, b"not really audio")?;
+        let transcriber = RemoteTranscriber::new(RemoteConfig {
+            base_url: format!("{base_url}/"),
+            model: "large-v3".to_string(),
+            api_key: Some("secret".to_string()),
+            ..RemoteConfig::default()
+        })?;
+        let options = TranscribeOptions {
+            prompt: Some("Kenobi".to_string()),
+            language: Some("en".to_string()),
+            ..TranscribeOptions::default()
+        };
+        let transcript = transcriber.transcribe(&audio, None, &options)?;
+        let (request_line, body) = server.join().expect("server thread");
+
+        assert_eq!(request_line, "POST /v1/audio/transcriptions HTTP/1.1");
+        assert!(body.contains("filename=\"call.m4a\""));
+        assert!(body.contains("not really audio"));
+        assert!(body.contains("name=\"model\"\r\n\r\nlarge-v3\r\n"));
+        assert!(body.contains("name=\"language\"\r\n\r\nen\r\n"));
+        assert!(body.contains("name=\"prompt\"\r\n\r\nKenobi\r\n"));
+        assert!(body.contains("verbose_json"));
+
+        assert_eq!(transcript.text(), "Hello there. General Kenobi.");
+        assert_eq!(transcript.segments[1].start_ms, 1_200);
+        assert_eq!(transcript.segments[1].no_speech_prob, Some(0.01));
+        assert_eq!(transcript.segments[1].words.len(), 2);
+        assert_eq!(transcript.segments[1].words[0].text, "General");
+        assert_eq!(
+            transcript.language,
+            Some(DetectedLanguage {
+                code: "en".to_string(),
+                probability: 1.0,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn detected_languages_outside_the_allow_list_are_rejected() -> Result<()> {
+        let options = TranscribeOptions {
+            language: Some("auto".to_string()),
+            languages: vec!["en".to_string(), "ru".to_string()],
+            ..TranscribeOptions::default()
+        };
+        let transcribe = |body| -> Result<Transcript> {
+            let (base_url, server) = stand_in_server("200 OK", body);
+            let transcriber = RemoteTranscriber::new(RemoteConfig {
+                base_url,
+                ..RemoteConfig::default()
+            })?;
+            let transcript = transcriber.transcribe_samples(vec![0.0; 1_600], &options);
+            let (_, request) = server.join().expect("server thread");
+            assert!(!request.contains("name=\"language\""));
+            transcript
+        };
+
+        let transcript = transcribe(r#"{"language":"russian","text":" Привет."}"#)?;
+        assert_eq!(transcript.language.map(|language| language.code).as_deref(), Some("ru"));
+        let err = transcribe(r#"{"language":"fr","text":" Bonjour."}"#)
+            .expect_err("language outside the allow-list");
+        assert!(err.to_string().contains("`fr`, which is not in `languages`"), "{err}");
+        let err = transcribe(r#"{"text":" Hi."}"#).expect_err("no language reported");
+        assert!(err.to_string().contains("did not report a language"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn server_errors_surface_the_response_body() -> Result<()> {
+        let (base_url, server) =
+            stand_in_server("503 Service Unavailable", r#"{"error":"model is loading"}"#);
+        let transcriber = RemoteTranscriber::new(RemoteConfig {
+            base_url,
+            ..RemoteConfig::default()
+        })?;
+        let options = TranscribeOptions {
+            translate: true,
+            ..TranscribeOptions::default()
+        };
+        let err = transcriber
+            .transcribe_samples(vec![0.0; 1_600], &options)
+            .expect_err("server error");
+        let (request_line, body) = server.join().expect("server thread");
+        assert_eq!(request_line, "POST /v1/audio/translations HTTP/1.1");
+        assert!(body.contains("filename=\"audio.wav\""));
+        assert!(err.to_string().contains("model is loading"));
+        Ok(())
+    }
+
+    #[test]
+    fn recordings_are_sent_as_16k_mono_without_touching_disk() -> Result<()> {
+        let (base_url, server) = stand_in_server("200 OK", r#"{"text":" Hi."}"#);
+        let transcriber = RemoteTranscriber::new(RemoteConfig {
+            base_url,
+            ..RemoteConfig::default()
+        })?;
+        let recorded = RecordedAudio {
+            samples: vec![0.1; 48_000 * 2],
+            sample_rate: 48_000,
+            channels: 2,
+        };
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let progress: Progress = Box::new(move |pct| sink.lock().expect("progress").push(pct));
+        let transcript = transcriber.transcribe_recording(
+            &recorded,
+            Some(progress),
+            &TranscribeOptions::default(),
+        )?;
+        let (_, body) = server.join().expect("server thread");
+        // One second of 16-bit mono at 16 kHz is a 32000-byte data chunk.
+        let data_len = 32_000u32.to_le_bytes();
+        assert!(body.contains(&format!("data{}", std::str::from_utf8(&data_len)?)));
+        assert_eq!(transcript.text(), "Hi.");
+        assert_eq!(*reported.lock().expect("progress"), vec![0, 100]);
+        Ok(())
+    }
+
+    #[test]
+    fn cancelled_jobs_never_reach_the_server() -> Result<()> {
+        let transcriber = RemoteTranscriber::new(RemoteConfig {
+            // Nothing listens here; a request would fail with a connection error.
+            base_url: "http://127.0.0.1:9".to_string(),
+            ..RemoteConfig::default()
+        })?;
+        let options = TranscribeOptions::default();
+        options.cancel.cancel();
+        let err = transcriber
+            .transcribe_samples(vec![0.0; 1_600], &options)
+            .expect_err("cancelled");
+        assert!(err.is::<Cancelled>());
+        Ok(())
+    }
+}
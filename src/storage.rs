@@ -1,3 +1,4 @@
+use crate::journal::JOURNAL_EXTENSION;
 use anyhow::{Context, Result};
 use chrono::Local;
 use std::fs;
@@ -13,32 +14,60 @@ pub fn iso_timestamp() -> String {
     now.format("%Y-%m-%dT%H-%M-%S%.3f%z").to_string()
 }
 
-pub fn next_recording_paths(recordings_dir: &Path) -> Result<(PathBuf, PathBuf)> {
+/// Audio (with `extension`) and transcript paths for a new recording.
+pub fn next_recording_paths(
+    recordings_dir: &Path,
+    extension: &str,
+) -> Result<(PathBuf, PathBuf)> {
     ensure_dir(recordings_dir)?;
     let stamp = iso_timestamp();
-    let audio = recordings_dir.join(format!("{stamp}.m4a"));
+    let audio = recordings_dir.join(format!("{stamp}.{extension}"));
     let text = recordings_dir.join(format!("{stamp}.md"));
     Ok((audio, text))
 }
 
+/// Journal for a recording that is about to start.
+pub fn next_journal_path(recordings_dir: &Path) -> Result<PathBuf> {
+    ensure_dir(recordings_dir)?;
+    Ok(recordings_dir.join(format!("{}.{JOURNAL_EXTENSION}", iso_timestamp())))
+}
+
+/// Audio and transcript paths for the recording captured in `journal`.
+pub fn recording_paths_for_journal(journal: &Path, extension: &str) -> (PathBuf, PathBuf) {
+    (journal.with_extension(extension), journal.with_extension("md"))
+}
+
 pub fn transcript_path_for_input(input: &Path) -> Result<PathBuf> {
-    let parent = input
-        .parent()
-        .context("input file has no parent directory")?;
-    let stem = input
-        .file_stem()
-        .context("input file has no filename")?
-        .to_string_lossy();
-    Ok(parent.join(format!("{stem}.md")))
+    output_path_for_input(input, None, "md")
 }
 
 pub fn transcript_path_for_output_dir(input: &Path, output_dir: &Path) -> Result<PathBuf> {
+    output_path_for_input(input, Some(output_dir), "md")
+}
+
+/// Transcript path for `input` with the given extension, next to the input unless
+/// `output_dir` is set.
+pub fn output_path_for_input(
+    input: &Path,
+    output_dir: Option<&Path>,
+    extension: &str,
+) -> Result<PathBuf> {
     let stem = input
         .file_stem()
         .context("input file has no filename")?
         .to_string_lossy();
-    let safe = sanitize_filename_component(&stem);
-    Ok(output_dir.join(format!("{safe}.md")))
+    match output_dir {
+        Some(output_dir) => {
+            let safe = sanitize_filename_component(&stem);
+            Ok(output_dir.join(format!("{safe}.{extension}")))
+        }
+        None => {
+            let parent = input
+                .parent()
+                .context("input file has no parent directory")?;
+            Ok(parent.join(format!("{stem}.{extension}")))
+        }
+    }
 }
 
 pub fn processed_path_for_input(input: &Path, processed_dir: &Path) -> Result<PathBuf> {
@@ -78,7 +107,7 @@ mod tests {
     #[test]
     fn next_recording_paths_create_in_dir() -> Result<()> {
         let dir = tempdir()?;
-        let (audio, text) = next_recording_paths(dir.path())?;
+        let (audio, text) = next_recording_paths(dir.path(), "m4a")?;
         assert!(audio.starts_with(dir.path()));
         assert!(text.starts_with(dir.path()));
         assert!(audio.extension().unwrap_or_default() == "m4a");
@@ -86,6 +115,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn journal_paths_share_the_recording_stamp() -> Result<()> {
+        let dir = tempdir()?;
+        let journal = next_journal_path(dir.path())?;
+        assert_eq!(journal.extension().unwrap_or_default(), JOURNAL_EXTENSION);
+        let (audio, text) = recording_paths_for_journal(&journal, "flac");
+        assert_eq!(audio.file_stem(), journal.file_stem());
+        assert_eq!(audio.extension().unwrap_or_default(), "flac");
+        assert_eq!(text, journal.with_extension("md"));
+        Ok(())
+    }
+
     #[test]
     fn transcript_path_for_output_dir_uses_stem() -> Result<()> {
         let dir = tempdir()?;
@@ -96,6 +137,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn output_path_for_input_uses_extension_and_dir() -> Result<()> {
+        let dir = tempdir()?;
+        let input = dir.path().join("call.m4a");
+        let beside = output_path_for_input(&input, None, "srt")?;
+        assert_eq!(beside, dir.path().join("call.srt"));
+        let output_dir = dir.path().join("subs");
+        let out = output_path_for_input(&input, Some(&output_dir), "vtt")?;
+        assert_eq!(out, output_dir.join("call.vtt"));
+        Ok(())
+    }
+
     #[test]
     fn processed_path_for_input_preserves_filename() -> Result<()> {
         let dir = tempdir()?;
@@ -1,28 +1,278 @@
+use crate::audio::RecordedAudio;
+use crate::decode::{downmix_to_mono, AudioDecoder, StreamingResampler};
+use crate::filter::HallucinationFilter;
+use crate::preprocess::{preprocess, PreprocessConfig};
+use crate::transcript::{ComputeDevice, DetectedLanguage, Segment, Transcript, Word};
+use crate::vad::{
+    estimate_no_speech, frame_energies, prefilter_speech, trim_silence, TimeMap, VadConfig,
+};
 use anyhow::{Context, Result};
-use rubato::{Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::ffi::CStr;
-use std::fs::File;
+use std::fmt;
 use std::os::raw::{c_char, c_void};
 use std::path::{Path, PathBuf};
 use std::cmp::Ordering;
-use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, Once};
+use std::time::Instant;
 use std::{env, fs};
-use symphonia::core::audio::SampleBuffer;
-use symphonia::core::codecs::DecoderOptions;
-use symphonia::core::errors::Error as SymphoniaError;
-use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
-use symphonia::core::meta::MetadataOptions;
-use symphonia::core::probe::Hint;
 
+/// Progress callback handed to a backend; receives percentages in 0..=100.
+pub type Progress = Box<dyn FnMut(i32) + Send>;
+
+/// Progress callback shared between the windows and channels of one file.
+type SharedProgress = Arc<dyn Fn(i32) + Send + Sync>;
+
+/// A speech-to-text backend the app and CLI run jobs on.
+pub trait Transcriber: Send + Sync {
+    /// Transcribe an audio file, reporting progress as it goes.
+    fn transcribe(
+        &self,
+        path: &Path,
+        progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>;
+
+    /// Transcribe 16 kHz mono samples; segment times are relative to the first sample.
+    fn transcribe_samples(
+        &self,
+        samples_16k: Vec<f32>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>;
+
+    /// Transcribe a capture still in memory, without an encode/decode round trip.
+    /// Channels are downmixed; `split_channels` only applies to files.
+    fn transcribe_recording(
+        &self,
+        recorded: &RecordedAudio,
+        progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>;
+}
+
+/// Whisper backend that loads the model on first use and keeps it resident, so
+/// repeated jobs only pay for a fresh inference state.
 pub struct WhisperTranscriber {
     model_path: PathBuf,
+    contexts: Mutex<LoadedContexts>,
+}
+
+#[derive(Default)]
+struct LoadedContexts {
+    gpu: Option<Arc<whisper_rs::WhisperContext>>,
+    cpu: Option<Arc<whisper_rs::WhisperContext>>,
+    gpu_failed: bool,
+}
+
+/// whisper-rs is built with the Metal backend only; elsewhere a "GPU" context
+/// silently runs on the CPU.
+const GPU_BACKEND: bool = cfg!(target_os = "macos");
+
+/// Where local inference runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Device {
+    /// GPU when this build has a GPU backend, falling back to CPU if it fails.
+    #[default]
+    Auto,
+    Cpu,
+    Gpu,
+}
+
+impl Device {
+    fn prefers_gpu(self) -> bool {
+        match self {
+            Device::Auto => GPU_BACKEND,
+            Device::Gpu => true,
+            Device::Cpu => false,
+        }
+    }
+}
+
+/// Decoder settings: beam search for accuracy, or a greedy single-segment pass
+/// over a shortened encoder context for short dictations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyMode {
+    /// Fast for clips shorter than 15 seconds, accurate otherwise.
+    #[default]
+    Auto,
+    Fast,
+    Accurate,
+}
+
+impl LatencyMode {
+    fn resolve(self, samples_16k: usize) -> Self {
+        match self {
+            LatencyMode::Auto if samples_16k < LOW_LATENCY_MAX_SAMPLES => LatencyMode::Fast,
+            LatencyMode::Auto => LatencyMode::Accurate,
+            mode => mode,
+        }
+    }
+}
+
+/// Shared flag that stops a running job: checked between windows and polled by
+/// whisper's abort callback during inference.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::SeqCst)
+    }
+
+    /// `Err(Cancelled)` once the token has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by a job stopped through its `CancelToken`; test for it with
+/// `err.is::<Cancelled>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("transcription cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    pub prompt: Option<String>,
+    pub language: Option<String>,
+    /// Translate the speech to English instead of transcribing it verbatim.
+    pub translate: bool,
+    /// Language codes detection may choose from; empty allows any.
+    pub languages: Vec<String>,
+    /// Signal conditioning applied before voice activity detection.
+    pub preprocess: PreprocessConfig,
+    /// Voice activity detection used to cut silence before inference.
+    pub vad: VadConfig,
+    /// Transcribe each channel of a file on its own and label it as a speaker.
+    pub split_channels: bool,
+    /// Cleanup applied to whatever the backend returns.
+    pub filter: HallucinationFilter,
+    pub device: Device,
+    /// Inference threads; `None` uses every core.
+    pub threads: Option<usize>,
+    /// Run inference again on the CPU when the GPU returns no segments.
+    pub retry_on_cpu: bool,
+    pub latency: LatencyMode,
+    pub cancel: CancelToken,
+}
+
+impl TranscribeOptions {
+    /// Whether whisper has to pick the language itself: `auto`, or translating
+    /// without a known source language. English is the translation target, so
+    /// translating from `en` means the source is unknown too.
+    pub(crate) fn detects_language(&self) -> bool {
+        match self.language.as_deref().map(str::trim) {
+            Some(language) if language.eq_ignore_ascii_case("auto") => true,
+            Some(language) if language.eq_ignore_ascii_case("en") => self.translate,
+            Some("") | None => self.translate,
+            Some(_) => false,
+        }
+    }
 }
 
 impl WhisperTranscriber {
     pub fn new(model_path: PathBuf) -> Result<Self> {
         init_whisper_runtime();
-        Ok(Self { model_path })
+        Ok(Self {
+            model_path,
+            contexts: Mutex::new(LoadedContexts::default()),
+        })
+    }
+
+    pub fn model_path(&self) -> &Path {
+        &self.model_path
+    }
+
+    /// Load the model ahead of the first job so it is not on the critical path.
+    pub fn preload(&self, device: Device) -> Result<()> {
+        let _silence = StderrSilencer::new();
+        self.context_for(device)?;
+        Ok(())
+    }
+
+    /// Context for `device`, falling back to the CPU when `Auto` cannot load on the GPU.
+    fn context_for(&self, device: Device) -> Result<(Arc<whisper_rs::WhisperContext>, bool)> {
+        if !device.prefers_gpu() {
+            return Ok((self.context(false)?, false));
+        }
+        match self.context(true) {
+            Ok(ctx) => Ok((ctx, true)),
+            Err(err) if device == Device::Auto => {
+                tracing::debug!(error = %err, "gpu model load failed; using cpu");
+                Ok((self.context(false)?, false))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn context(&self, use_gpu: bool) -> Result<Arc<whisper_rs::WhisperContext>> {
+        let mut contexts = self
+            .contexts
+            .lock()
+            .map_err(|_| anyhow::anyhow!("whisper model lock poisoned"))?;
+        if use_gpu && !GPU_BACKEND {
+            anyhow::bail!("this build has no GPU backend; use --device cpu");
+        }
+        if use_gpu && contexts.gpu_failed {
+            anyhow::bail!("gpu model load failed earlier");
+        }
+        let cached = if use_gpu { &contexts.gpu } else { &contexts.cpu };
+        if let Some(ctx) = cached {
+            return Ok(Arc::clone(ctx));
+        }
+        let model_path = self
+            .model_path
+            .to_str()
+            .context("model path not valid utf-8")?;
+        let started = Instant::now();
+        let mut ctx_params = whisper_rs::WhisperContextParameters::default();
+        ctx_params.use_gpu(use_gpu);
+        let ctx = match whisper_rs::WhisperContext::new_with_params(model_path, ctx_params) {
+            Ok(ctx) => Arc::new(ctx),
+            Err(err) => {
+                if use_gpu {
+                    contexts.gpu_failed = true;
+                }
+                return Err(err).with_context(|| format!("load whisper model {model_path}"));
+            }
+        };
+        unsafe {
+            set_metal_log_callback();
+        }
+        tracing::info!(
+            model = %model_path,
+            use_gpu,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "loaded whisper model"
+        );
+        if use_gpu {
+            contexts.gpu = Some(Arc::clone(&ctx));
+        } else {
+            contexts.cpu = Some(Arc::clone(&ctx));
+        }
+        Ok(ctx)
     }
 
     pub fn transcribe_file(&self, path: &Path) -> Result<String> {
@@ -60,80 +310,337 @@ impl WhisperTranscriber {
         // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
         F: FnMut(i32) + Send + 'static,
     {
-        tracing::debug!(path = %path.display(), "decoding audio");
-        let (samples, sample_rate) = decode_to_mono_f32(path)?;
-        let raw_duration = if sample_rate == 0 {
-            0.0
-        } else {
-            samples.len() as f32 / sample_rate as f32
+        let options = TranscribeOptions {
+            prompt: prompt.map(str::to_string),
+            language: language.map(str::to_string),
+            ..TranscribeOptions::default()
         };
-        tracing::debug!(
+        let transcript = self.transcribe_file_structured(path, progress, &options)?;
+        Ok(transcript.text())
+    }
+
+    pub fn transcribe_file_structured<F>(
+        &self,
+        path: &Path,
+        progress: Option<F>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>
+    where
+        // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
+        F: FnMut(i32) + Send + 'static,
+    {
+        tracing::debug!(path = %path.display(), "decoding audio");
+        let decoder = AudioDecoder::open(path)?;
+        let progress = progress.map(shared_progress);
+        let channels = decoder.channels().unwrap_or(1);
+        if !options.split_channels || channels < 2 {
+            if options.split_channels {
+                tracing::debug!(
+                    channels,
+                    "not enough channels to split; transcribing as one"
+                );
+            }
+            return self.transcribe_decoder(decoder, progress, options);
+        }
+
+        tracing::debug!(channels, "transcribing channels as separate speakers");
+        let mut first = Some(decoder);
+        let mut transcripts = Vec::with_capacity(channels);
+        for channel in 0..channels {
+            options.cancel.check()?;
+            let mut decoder = match first.take() {
+                Some(decoder) => decoder,
+                None => AudioDecoder::open(path)?,
+            };
+            decoder.select_channel(channel);
+            let channel_progress = progress.clone().map(|progress| {
+                Arc::new(move |pct: i32| {
+                    progress((channel as i32 * 100 + pct.clamp(0, 100)) / channels as i32)
+                }) as SharedProgress
+            });
+            transcripts.push(
+                self.transcribe_decoder(decoder, channel_progress, options)
+                    .with_context(|| format!("transcribe channel {}", channel + 1))?,
+            );
+        }
+        Ok(Transcript::merge_speakers(transcripts))
+    }
+
+    /// Downmix and resample a recording in memory and transcribe it like a decoded
+    /// file, window by window.
+    pub fn transcribe_recorded<F>(
+        &self,
+        recorded: &RecordedAudio,
+        progress: Option<F>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>
+    where
+        // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
+        F: FnMut(i32) + Send + 'static,
+    {
+        let mono = downmix_to_mono(&recorded.samples, recorded.channels);
+        self.transcribe_stream(
+            recorded.sample_rate,
+            Some(mono.len() as u64),
+            mono_chunks(&mono),
+            progress.map(shared_progress),
+            options,
+        )
+    }
+
+    fn transcribe_decoder(
+        &self,
+        mut decoder: AudioDecoder,
+        progress: Option<SharedProgress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        let sample_rate = decoder.sample_rate();
+        let total_frames = decoder.total_frames();
+        self.transcribe_stream(
             sample_rate,
-            samples = samples.len(),
-            duration_sec = raw_duration,
-            "decoded audio"
-        );
-        let samples_16k = resample_to_16k(samples, sample_rate)?;
-        let duration_16k = samples_16k.len() as f32 / 16_000.0;
+            total_frames,
+            || decoder.next_chunk(),
+            progress,
+            options,
+        )
+    }
+
+    /// Resample mono chunks from `next_chunk` and transcribe them in bounded windows.
+    fn transcribe_stream(
+        &self,
+        sample_rate: u32,
+        total_frames: Option<u64>,
+        next_chunk: impl FnMut() -> Result<Option<Vec<f32>>>,
+        progress: Option<SharedProgress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        let expected_16k =
+            total_frames.map(|frames| (frames * 16_000 / sample_rate as u64) as usize);
+        tracing::debug!(sample_rate, total_frames = ?total_frames, "streaming audio");
+
+        let mut transcript = Transcript::default();
+        let total = stream_windows(
+            sample_rate,
+            stream_window_samples(inference_threads(options)),
+            next_chunk,
+            &options.cancel,
+            |samples, window_start, whole_stream| {
+                let window_len = samples.len();
+                tracing::debug!(
+                    start_sec = window_start as f32 / 16_000.0,
+                    duration_sec = window_len as f32 / 16_000.0,
+                    "transcribing audio window"
+                );
+                let mut window_options = options.clone();
+                window_options.prompt =
+                    continuation_prompt(options.prompt.as_deref(), &transcript.text());
+                if !whole_stream && options.latency == LatencyMode::Auto {
+                    // A short last window of a long file is not a short dictation.
+                    window_options.latency = LatencyMode::Accurate;
+                }
+                let window_progress = progress.clone().map(|progress| {
+                    move |pct: i32| {
+                        let pct = match expected_16k {
+                            Some(total) if total > 0 => {
+                                let done =
+                                    window_start + window_len * pct.clamp(0, 100) as usize / 100;
+                                (done * 100 / total).min(100) as i32
+                            }
+                            _ => pct,
+                        };
+                        progress(pct);
+                    }
+                });
+                if let Some(language) = &transcript.language {
+                    // Keep later windows in the language detected for the first one.
+                    window_options.language = Some(language.code.clone());
+                }
+                let part = self.transcribe_mono_16k(samples, window_progress, &window_options)?;
+                transcript.extend_shifted(part, window_start as u64 * 1000 / 16_000);
+                Ok(())
+            },
+        )?;
         tracing::debug!(
-            samples = samples_16k.len(),
-            duration_sec = duration_16k,
-            "resampled audio"
+            samples = total,
+            duration_sec = total as f32 / 16_000.0,
+            "decoded and resampled audio"
         );
-        let mut samples_16k = samples_16k;
-        if let Some(vad) = prefilter_speech(&mut samples_16k, 16_000) {
-            tracing::debug!(
-                removed_samples = vad.removed_samples,
-                kept_samples = vad.kept_samples,
-                removed_sec = vad.removed_samples as f32 / 16_000.0,
-                segments = vad.segments,
-                threshold = vad.threshold,
-                noise_floor = vad.noise_floor,
-                keep_silence_ms = vad.keep_silence_ms,
-                pad_ms = vad.pad_ms,
-                "prefiltered non-speech"
-            );
+        Ok(transcript)
+    }
+
+    /// Transcribe 16 kHz mono samples already in memory; segment times are
+    /// relative to the start of `samples_16k`.
+    pub fn transcribe_mono_16k<F>(
+        &self,
+        samples_16k: Vec<f32>,
+        progress: Option<F>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>
+    where
+        // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
+        F: FnMut(i32) + Send + 'static,
+    {
+        options.cancel.check()?;
+        let (samples_16k, time_map, options) = prepare_for_inference(samples_16k, options);
+        if samples_16k.is_empty() {
+            tracing::debug!("audio is silent after trimming; skipping inference");
+            return Ok(Transcript::default());
         }
-        if let Some(trim) = trim_silence(&mut samples_16k, 16_000) {
-            tracing::debug!(
-                trimmed_samples = trim.trimmed_samples,
-                trimmed_leading_samples = trim.trimmed_leading_samples,
-                trimmed_trailing_samples = trim.trimmed_trailing_samples,
-                trimmed_sec = trim.trimmed_samples as f32 / 16_000.0,
-                threshold = trim.threshold,
-                noise_floor = trim.noise_floor,
-                leading_frames = trim.leading_frames,
-                trailing_frames = trim.trailing_frames,
-                "trimmed leading/trailing silence"
+        let mut transcript = self.transcribe_buffer(&samples_16k, progress, &options)?;
+        // whisper-rs has no per-segment no-speech probability; estimate it for the filter.
+        let detector = options.vad.detector();
+        estimate_no_speech(&mut transcript, &samples_16k, 16_000, detector.as_ref());
+        time_map.remap_transcript(&mut transcript, 16_000);
+        options.filter.apply(&mut transcript);
+        Ok(transcript)
+    }
+
+    /// Resolve the language once for the whole buffer, then run inference.
+    fn transcribe_buffer<F>(
+        &self,
+        samples: &[f32],
+        progress: Option<F>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript>
+    where
+        // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
+        F: FnMut(i32) + Send + 'static,
+    {
+        let cores = inference_threads(options);
+        let mut options = options.clone();
+        let mut detected = None;
+        if options.detects_language() {
+            let language =
+                self.detect_language(samples, cores, &options.languages, options.device)?;
+            options.cancel.check()?;
+            tracing::info!(
+                language = %language.code,
+                probability = language.probability,
+                "detected language"
             );
+            options.language = Some(language.code.clone());
+            detected = Some(language);
         }
-        if samples_16k.is_empty() {
-            tracing::debug!("audio is silent after trimming; skipping inference");
-            return Ok(String::new());
+        let mut transcript = self.transcribe_chunks(samples, progress, &options, cores)?;
+        transcript.language = detected;
+        Ok(transcript)
+    }
+
+    /// Detect the spoken language from the first 30 s, restricted to `allowed`
+    /// codes when any are given.
+    fn detect_language(
+        &self,
+        samples: &[f32],
+        threads: usize,
+        allowed: &[String],
+        device: Device,
+    ) -> Result<DetectedLanguage> {
+        let _silence = StderrSilencer::new();
+        let (ctx, _) = self.context_for(device)?;
+        let mut state = ctx.create_state().context("create whisper state")?;
+        state
+            .pcm_to_mel(&samples[..samples.len().min(16_000 * 30)], threads)
+            .context("compute mel spectrogram")?;
+        let probs = state
+            .lang_detect(0, threads)
+            .context("detect language")?;
+        pick_language(&probs, allowed, whisper_rs::get_lang_str)
+            .context("no allowed language could be detected")
+    }
+
+    /// Split long buffers at silence and run the chunks on separate whisper states
+    /// so many-core machines are kept busy; segments are stitched back in order.
+    fn transcribe_chunks<F>(
+        &self,
+        samples: &[f32],
+        progress: Option<F>,
+        options: &TranscribeOptions,
+        cores: usize,
+    ) -> Result<Transcript>
+    where
+        // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
+        F: FnMut(i32) + Send + 'static,
+    {
+        let parts = (cores / PARALLEL_MIN_THREADS_PER_CHUNK)
+            .min(samples.len() / PARALLEL_MIN_CHUNK_SAMPLES)
+            .max(1);
+        let cuts = silence_split_points(samples, parts);
+        if cuts.is_empty() {
+            return self.transcribe_samples_with_progress(samples, progress, options, cores);
+        }
+        let mut ranges = Vec::with_capacity(cuts.len() + 1);
+        let mut start = 0usize;
+        for cut in cuts.into_iter().chain(std::iter::once(samples.len())) {
+            ranges.push((start, cut));
+            start = cut;
+        }
+        let threads = (cores / ranges.len()).max(1);
+        tracing::debug!(
+            chunks = ranges.len(),
+            threads_per_chunk = threads,
+            "running whisper chunks in parallel"
+        );
+
+        let progress = progress.map(|progress| Arc::new(Mutex::new(progress)));
+        let chunk_done = Arc::new(Mutex::new(vec![0usize; ranges.len()]));
+        let total = samples.len().max(1);
+        let results: Vec<Result<Transcript>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = ranges
+                .iter()
+                .enumerate()
+                .map(|(idx, &(start, end))| {
+                    let chunk_progress = progress.clone().map(|progress| {
+                        let chunk_done = Arc::clone(&chunk_done);
+                        move |pct: i32| {
+                            let overall = match chunk_done.lock() {
+                                Ok(mut done) => {
+                                    done[idx] = (end - start) * pct.clamp(0, 100) as usize / 100;
+                                    done.iter().sum::<usize>() * 100 / total
+                                }
+                                Err(_) => return,
+                            };
+                            if let Ok(mut progress) = progress.lock() {
+                                (*progress)(overall as i32);
+                            }
+                        }
+                    });
+                    let chunk = &samples[start..end];
+                    scope.spawn(move || {
+                        self.transcribe_samples_with_progress(chunk, chunk_progress, options, threads)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("whisper chunk thread panicked")))
+                })
+                .collect()
+        });
+
+        let mut transcript = Transcript::default();
+        for (&(start, _), result) in ranges.iter().zip(results) {
+            transcript.extend_shifted(result?, start as u64 * 1000 / 16_000);
         }
-        self.transcribe_samples_with_progress(&samples_16k, progress, prompt, language)
+        Ok(transcript)
     }
 
     fn transcribe_samples_with_progress<F>(
         &self,
         samples: &[f32],
         progress: Option<F>,
-        prompt: Option<&str>,
-        language: Option<&str>,
-    ) -> Result<String>
+        options: &TranscribeOptions,
+        threads: usize,
+    ) -> Result<Transcript>
     where
         // Progress callbacks can be invoked from non-main threads; keep them Send to avoid UB.
         F: FnMut(i32) + Send + 'static,
     {
         let _silence = StderrSilencer::new();
-        let model_path = self
-            .model_path
-            .to_str()
-            .context("model path not valid utf-8")?;
-        let threads = std::thread::available_parallelism()
-            .map(|n| n.get() as i32)
-            .unwrap_or(4);
-        let prompt = prompt.and_then(|prompt| {
+        let threads = threads as i32;
+        let prompt = options.prompt.as_deref().and_then(|prompt| {
             let prompt = prompt.trim();
             if prompt.is_empty() {
                 None
@@ -155,7 +662,7 @@ impl WhisperTranscriber {
         } else {
             sum_abs / samples.len() as f32
         };
-        let language = language.and_then(|lang| {
+        let language = options.language.as_deref().and_then(|lang| {
             let lang = lang.trim();
             if lang.is_empty() {
                 None
@@ -166,6 +673,7 @@ impl WhisperTranscriber {
         let mut detect_language = false;
         let mut language_for_params = None;
         let mut language_label = "default-en";
+        let translate = options.translate;
         if let Some(language) = language {
             if language.eq_ignore_ascii_case("auto") {
                 detect_language = true;
@@ -177,21 +685,21 @@ impl WhisperTranscriber {
         }
         let prompt_len = prompt.map(|p| p.len()).unwrap_or(0);
         let duration_sec = samples.len() as f32 / 16_000.0;
-        let run_inference = |use_gpu: bool, progress: Option<F>| -> Result<(String, i32)> {
-            let mut ctx_params = whisper_rs::WhisperContextParameters::default();
-            ctx_params.use_gpu(use_gpu);
-            let ctx = whisper_rs::WhisperContext::new_with_params(model_path, ctx_params)
-                .with_context(|| format!("load whisper model {model_path}"))?;
-            unsafe {
-                set_metal_log_callback();
-            }
+        let fast = options.latency == LatencyMode::Fast;
+        let run_inference = |use_gpu: bool, progress: Option<F>| -> Result<Transcript> {
+            let ctx = self.context(use_gpu)?;
             let mut state = ctx
                 .create_state()
                 .context("create whisper state")?;
-            let mut params = whisper_rs::FullParams::new(whisper_rs::SamplingStrategy::BeamSearch {
-                beam_size: 5,
-                patience: 1.0,
-            });
+            let strategy = if fast {
+                whisper_rs::SamplingStrategy::Greedy { best_of: 1 }
+            } else {
+                whisper_rs::SamplingStrategy::BeamSearch {
+                    beam_size: 5,
+                    patience: 1.0,
+                }
+            };
+            let mut params = whisper_rs::FullParams::new(strategy);
             params.set_n_threads(threads);
             params.set_suppress_blank(true);
             params.set_suppress_non_speech_tokens(true);
@@ -200,68 +708,83 @@ impl WhisperTranscriber {
             params.set_logprob_thold(-1.0);
             params.set_entropy_thold(2.4);
             params.set_no_speech_thold(0.6);
+            params.set_token_timestamps(true);
+            params.set_translate(translate);
             if let Some(prompt) = prompt {
                 params.set_initial_prompt(prompt);
             }
+            if fast {
+                params.set_single_segment(true);
+                params.set_no_context(true);
+                params.set_audio_ctx(low_latency_audio_ctx(samples.len()));
+            }
             tracing::debug!(
-                model = %model_path,
+                model = %self.model_path.display(),
                 threads,
                 prompt_len,
                 language = language_label,
                 detect_language,
+                translate,
                 duration_sec,
                 max_abs,
                 avg_abs,
                 use_gpu,
+                fast,
                 "starting whisper inference"
             );
             params.set_progress_callback_safe::<Option<F>, F>(progress);
+            // whisper.cpp treats a null language as "detect, then transcribe"; the
+            // detect_language flag would stop right after detection.
             if detect_language {
                 params.set_language(None);
-                params.set_detect_language(true);
+                params.set_detect_language(false);
             } else if let Some(language) = language_for_params {
                 params.set_language(Some(language));
                 params.set_detect_language(false);
             }
-            state
-                .full(params, samples)
-                .context("whisper inference")?;
-
-            let num_segments = state.full_n_segments().context("segment count")?;
-            let mut out = String::new();
-            for i in 0..num_segments {
-                let segment = state
-                    .full_get_segment_text(i)
-                    .context("segment text")?;
-                out.push_str(&segment);
+            // The token outlives this call: `options` is borrowed for the whole function.
+            unsafe {
+                params.set_abort_callback(Some(abort_if_cancelled));
+                params.set_abort_callback_user_data(
+                    Arc::as_ptr(&options.cancel.0) as *mut c_void
+                );
             }
-            Ok((out.trim().to_string(), num_segments))
+            let result = state.full(params, samples);
+            options.cancel.check()?;
+            result.context("whisper inference")?;
+
+            collect_segments(&ctx, &state)
         };
 
-        let mut used_gpu = true;
+        let mut used_gpu = options.device.prefers_gpu();
         let mut progress = progress;
-        let (mut text, mut num_segments) = match run_inference(true, progress.take()) {
+        let mut transcript = match run_inference(used_gpu, progress.take()) {
             Ok(result) => result,
-            Err(err) => {
+            Err(err) if used_gpu && options.device == Device::Auto && !err.is::<Cancelled>() => {
                 tracing::debug!(error = %err, "whisper inference failed with gpu; retrying on cpu");
                 used_gpu = false;
                 run_inference(false, None)?
             }
+            Err(err) => return Err(err),
         };
 
-        if num_segments == 0 && used_gpu {
+        if transcript.is_empty() && used_gpu && options.retry_on_cpu {
             tracing::debug!(
                 duration_sec,
                 max_abs,
                 avg_abs,
                 "whisper returned no segments with gpu; retrying on cpu"
             );
-            let (cpu_text, cpu_segments) = run_inference(false, None)?;
-            text = cpu_text;
-            num_segments = cpu_segments;
+            transcript = run_inference(false, None)?;
             used_gpu = false;
         }
 
+        transcript.device = Some(if used_gpu {
+            ComputeDevice::Gpu
+        } else {
+            ComputeDevice::Cpu
+        });
+        let num_segments = transcript.segments.len();
         if num_segments == 0 {
             tracing::debug!(
                 duration_sec,
@@ -273,12 +796,190 @@ impl WhisperTranscriber {
         } else {
             tracing::debug!(num_segments, use_gpu = used_gpu, "whisper returned segments");
         }
-        Ok(text)
+        Ok(transcript)
     }
 }
 
+impl Transcriber for WhisperTranscriber {
+    fn transcribe(
+        &self,
+        path: &Path,
+        progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        self.transcribe_file_structured(path, progress, options)
+    }
+
+    fn transcribe_samples(
+        &self,
+        samples_16k: Vec<f32>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        self.transcribe_mono_16k(samples_16k, None::<fn(i32)>, options)
+    }
+
+    fn transcribe_recording(
+        &self,
+        recorded: &RecordedAudio,
+        progress: Option<Progress>,
+        options: &TranscribeOptions,
+    ) -> Result<Transcript> {
+        self.transcribe_recorded(recorded, progress, options)
+    }
+}
+
+fn shared_progress<F>(progress: F) -> SharedProgress
+where
+    F: FnMut(i32) + Send + 'static,
+{
+    let progress = Mutex::new(progress);
+    Arc::new(move |pct: i32| {
+        if let Ok(mut progress) = progress.lock() {
+            (*progress)(pct);
+        }
+    })
+}
+
+/// Most probable language among `allowed` (any language when empty).
+fn pick_language(
+    probs: &[f32],
+    allowed: &[String],
+    lang_str: impl Fn(i32) -> Option<&'static str>,
+) -> Option<DetectedLanguage> {
+    probs
+        .iter()
+        .enumerate()
+        .filter_map(|(id, &probability)| {
+            let code = lang_str(id as i32)?;
+            let permitted = allowed.is_empty()
+                || allowed
+                    .iter()
+                    .any(|allowed| allowed.trim().eq_ignore_ascii_case(code));
+            permitted.then_some((code, probability))
+        })
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        .map(|(code, probability)| DetectedLanguage {
+            code: code.to_string(),
+            probability,
+        })
+}
+
+fn collect_segments(
+    ctx: &whisper_rs::WhisperContext,
+    state: &whisper_rs::WhisperState,
+) -> Result<Transcript> {
+    let num_segments = state.full_n_segments().context("segment count")?;
+    let token_eot = ctx.token_eot();
+    let mut segments = Vec::with_capacity(num_segments.max(0) as usize);
+    for i in 0..num_segments {
+        let text = state
+            .full_get_segment_text(i)
+            .context("segment text")?;
+        // whisper reports segment bounds in 10 ms units.
+        let t0 = state.full_get_segment_t0(i).context("segment start")?;
+        let t1 = state.full_get_segment_t1(i).context("segment end")?;
+        let start_ms = t0.max(0) as u64 * 10;
+        let end_ms = t1.max(0) as u64 * 10;
+        let num_tokens = state.full_n_tokens(i).context("segment token count")?;
+        let mut tokens = Vec::with_capacity(num_tokens.max(0) as usize);
+        for j in 0..num_tokens {
+            let token = state.full_get_token_data(i, j).context("token data")?;
+            // Special tokens (timestamps, sot/eot, language) carry no text.
+            if token.id >= token_eot {
+                continue;
+            }
+            let bytes = ctx
+                .token_to_cstr(token.id)
+                .context("token text")?
+                .to_bytes()
+                .to_vec();
+            // Token times are -1 when whisper could not align the token.
+            let token_start = if token.t0 >= 0 { token.t0 as u64 * 10 } else { start_ms };
+            let token_end = if token.t1 >= 0 { token.t1 as u64 * 10 } else { end_ms };
+            tokens.push(TokenPiece {
+                bytes,
+                start_ms: token_start,
+                end_ms: token_end,
+                probability: token.p,
+            });
+        }
+        let avg_token_prob = if tokens.is_empty() {
+            0.0
+        } else {
+            tokens.iter().map(|t| t.probability).sum::<f32>() / tokens.len() as f32
+        };
+        segments.push(Segment {
+            start_ms,
+            end_ms,
+            text,
+            avg_token_prob,
+            // The bundled whisper.cpp computes no per-segment no-speech probability.
+            no_speech_prob: None,
+            speaker: None,
+            words: group_words(&tokens),
+        });
+    }
+    Ok(Transcript::new(segments))
+}
+
+struct TokenPiece {
+    bytes: Vec<u8>,
+    start_ms: u64,
+    end_ms: u64,
+    probability: f32,
+}
+
+/// Merge BPE tokens into words: a token starting with a space opens a new word,
+/// anything else (word pieces, punctuation) extends the current one. Bytes are
+/// joined before decoding because multi-byte characters can span tokens.
+fn group_words(tokens: &[TokenPiece]) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut pending: Vec<&TokenPiece> = Vec::new();
+    for token in tokens {
+        if token.bytes.first() == Some(&b' ') && !pending.is_empty() {
+            words.extend(word_from_tokens(&pending));
+            pending.clear();
+        }
+        pending.push(token);
+    }
+    words.extend(word_from_tokens(&pending));
+    words
+}
+
+fn word_from_tokens(tokens: &[&TokenPiece]) -> Option<Word> {
+    let first = tokens.first()?;
+    let bytes: Vec<u8> = tokens
+        .iter()
+        .flat_map(|token| token.bytes.iter().copied())
+        .collect();
+    let text = String::from_utf8_lossy(&bytes).trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    let end_ms = tokens
+        .iter()
+        .map(|token| token.end_ms)
+        .max()
+        .unwrap_or(first.end_ms);
+    let probability =
+        tokens.iter().map(|token| token.probability).sum::<f32>() / tokens.len() as f32;
+    Some(Word {
+        start_ms: first.start_ms,
+        end_ms,
+        text,
+        probability,
+    })
+}
+
 static WHISPER_RUNTIME_INIT: Once = Once::new();
 
+unsafe extern "C" fn abort_if_cancelled(user_data: *mut c_void) -> bool {
+    if user_data.is_null() {
+        return false;
+    }
+    unsafe { (*(user_data as *const AtomicBool)).load(AtomicOrdering::SeqCst) }
+}
+
 fn init_whisper_runtime() {
     WHISPER_RUNTIME_INIT.call_once(|| {
         ensure_metal_resources();
@@ -320,32 +1021,51 @@ unsafe extern "C" fn whisper_log_silent(
 ) {
 }
 
-struct StderrSilencer {
-    #[cfg(unix)]
+/// Redirects stderr to /dev/null while whisper runs. Concurrent guards (preload,
+/// live and queued jobs) share one redirect; the last guard dropped restores it.
+struct StderrSilencer;
+
+#[cfg(unix)]
+struct SilencedStderr {
     original_fd: i32,
-    #[cfg(unix)]
     null_fd: i32,
+    guards: usize,
 }
 
+#[cfg(unix)]
+static SILENCED_STDERR: Mutex<Option<SilencedStderr>> = Mutex::new(None);
+
 impl StderrSilencer {
     fn new() -> Option<Self> {
         #[cfg(unix)]
-        unsafe {
-            let original_fd = libc::dup(libc::STDERR_FILENO);
-            if original_fd < 0 {
-                return None;
-            }
-            let null_fd = libc::open(b"/dev/null\0".as_ptr().cast(), libc::O_WRONLY);
-            if null_fd < 0 {
-                libc::close(original_fd);
-                return None;
+        {
+            let mut silenced = SILENCED_STDERR.lock().ok()?;
+            if let Some(state) = silenced.as_mut() {
+                state.guards += 1;
+                return Some(Self);
             }
-            if libc::dup2(null_fd, libc::STDERR_FILENO) < 0 {
-                libc::close(original_fd);
-                libc::close(null_fd);
-                return None;
+            unsafe {
+                let original_fd = libc::dup(libc::STDERR_FILENO);
+                if original_fd < 0 {
+                    return None;
+                }
+                let null_fd = libc::open(b"/dev/null\0".as_ptr().cast(), libc::O_WRONLY);
+                if null_fd < 0 {
+                    libc::close(original_fd);
+                    return None;
+                }
+                if libc::dup2(null_fd, libc::STDERR_FILENO) < 0 {
+                    libc::close(original_fd);
+                    libc::close(null_fd);
+                    return None;
+                }
+                *silenced = Some(SilencedStderr {
+                    original_fd,
+                    null_fd,
+                    guards: 1,
+                });
             }
-            Some(Self { original_fd, null_fd })
+            Some(Self)
         }
         #[cfg(not(unix))]
         {
@@ -357,10 +1077,21 @@ impl StderrSilencer {
 #[cfg(unix)]
 impl Drop for StderrSilencer {
     fn drop(&mut self) {
-        unsafe {
-            let _ = libc::dup2(self.original_fd, libc::STDERR_FILENO);
-            let _ = libc::close(self.original_fd);
-            let _ = libc::close(self.null_fd);
+        let Ok(mut silenced) = SILENCED_STDERR.lock() else {
+            return;
+        };
+        if let Some(state) = silenced.as_mut() {
+            state.guards -= 1;
+            if state.guards > 0 {
+                return;
+            }
+        }
+        if let Some(state) = silenced.take() {
+            unsafe {
+                let _ = libc::dup2(state.original_fd, libc::STDERR_FILENO);
+                let _ = libc::close(state.original_fd);
+                let _ = libc::close(state.null_fd);
+            }
         }
     }
 }
@@ -446,447 +1177,222 @@ fn ensure_metal_resources() {
     tracing::error!("metal resources not found; set GGML_METAL_PATH_RESOURCES");
 }
 
-fn decode_to_mono_f32(path: &Path) -> Result<(Vec<f32>, u32)> {
-    let file = File::open(path).with_context(|| format!("open audio {}", path.display()))?;
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
-    let mut hint = Hint::new();
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        hint.with_extension(ext);
-    }
-    let probed = symphonia::default::get_probe().format(
-        &hint,
-        mss,
-        &FormatOptions::default(),
-        &MetadataOptions::default(),
-    )?;
-    let mut format = probed.format;
-    let track = format.default_track().context("no default audio track")?;
-    let sample_rate = track
-        .codec_params
-        .sample_rate
-        .context("missing sample rate")?;
-
-    let mut decoder =
-        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
-
-    let mut mono = Vec::new();
-    loop {
-        let packet = match format.next_packet() {
-            Ok(packet) => packet,
-            Err(SymphoniaError::IoError(err))
-                if err.kind() == std::io::ErrorKind::UnexpectedEof =>
-            {
-                break;
-            }
-            Err(err) => return Err(err.into()),
-        };
-
-        let decoded = decoder.decode(&packet)?;
-        let spec = *decoded.spec();
-        let channels = spec.channels.count();
-        let duration = decoded.frames() as u64;
-        let mut sample_buf = SampleBuffer::<f32>::new(duration, spec);
-        sample_buf.copy_interleaved_ref(decoded);
-        let samples = sample_buf.samples();
-        if channels == 1 {
-            mono.extend_from_slice(samples);
-        } else {
-            let frames = samples.len() / channels;
-            for frame in 0..frames {
-                let mut sum = 0.0;
-                for ch in 0..channels {
-                    sum += samples[frame * channels + ch];
-                }
-                mono.push(sum / channels as f32);
-            }
-        }
-    }
-
-    Ok((mono, sample_rate))
-}
-
-fn resample_to_16k(input: Vec<f32>, sample_rate: u32) -> Result<Vec<f32>> {
-    if sample_rate == 16_000 {
-        return Ok(input);
-    }
-    let params = SincInterpolationParameters {
-        sinc_len: 128,
-        f_cutoff: 0.95,
-        interpolation: SincInterpolationType::Linear,
-        oversampling_factor: 128,
-        window: WindowFunction::BlackmanHarris2,
-    };
-    let mut resampler =
-        SincFixedIn::<f32>::new(16_000.0 / sample_rate as f64, 1.0, params, input.len(), 1)?;
-    let out = resampler.process(&[input], None)?;
-    Ok(out.into_iter().next().unwrap_or_default())
-}
-
-struct VadResult {
-    removed_samples: usize,
-    kept_samples: usize,
-    segments: usize,
-    threshold: f32,
-    noise_floor: f32,
-    keep_silence_ms: usize,
-    pad_ms: usize,
+/// Shortest stretch of 16 kHz audio handed to VAD and whisper at once; windows only
+/// grow past it to keep many cores busy, so memory stays flat however long the
+/// input is.
+const STREAM_WINDOW_SAMPLES: usize = 16_000 * 300;
+/// Frames of an in-memory recording handed to the resampler at a time.
+const RECORDING_CHUNK_FRAMES: usize = 16_384;
+
+/// How far back from a full window's end to look for a quiet place to cut.
+const STREAM_SPLIT_SEARCH_SAMPLES: usize = 16_000 * 30;
+/// Buffers shorter than this are never split for parallel inference.
+const PARALLEL_MIN_CHUNK_SAMPLES: usize = 16_000 * 60;
+/// Each parallel whisper state gets at least this many threads.
+const PARALLEL_MIN_THREADS_PER_CHUNK: usize = 4;
+/// Stream windows hold this many times the audio their parallel chunks need, since
+/// VAD usually cuts a good share of it as silence.
+const PARALLEL_WINDOW_HEADROOM: usize = 2;
+/// How far a parallel cut may move from its even-split target to find silence.
+const PARALLEL_SPLIT_SEARCH_SAMPLES: usize = 16_000 * 15;
+/// Tail of earlier text passed as prompt so the next chunk continues the sentence.
+const CONTINUATION_PROMPT_CHARS: usize = 200;
+
+/// Clips shorter than this take the fast path when latency is `auto`.
+const LOW_LATENCY_MAX_SAMPLES: usize = 16_000 * 15;
+/// Encoder frames per second of audio (1500 for whisper's 30 s window).
+const AUDIO_CTX_PER_SEC: usize = 50;
+/// Encoder frames added past the clip's end; much tighter contexts make whisper
+/// drop or invent the last words.
+const AUDIO_CTX_MARGIN: usize = 128;
+
+/// Window length that lets `transcribe_chunks` give every core a chunk: room for
+/// `cores / PARALLEL_MIN_THREADS_PER_CHUNK` minimum-length chunks once VAD has cut
+/// the silence, and never below `STREAM_WINDOW_SAMPLES`.
+fn stream_window_samples(cores: usize) -> usize {
+    let parts = cores / PARALLEL_MIN_THREADS_PER_CHUNK;
+    (parts * PARALLEL_MIN_CHUNK_SAMPLES * PARALLEL_WINDOW_HEADROOM).max(STREAM_WINDOW_SAMPLES)
 }
 
-fn speech_median(energies: &[f32], threshold: f32) -> Option<f32> {
-    let mut speech: Vec<f32> = energies.iter().copied().filter(|&e| e >= threshold).collect();
-    if speech.len() < 3 {
-        return None;
-    }
-    speech.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    Some(speech[speech.len() / 2])
+/// Inference threads for `options`: the configured count, or every core.
+fn inference_threads(options: &TranscribeOptions) -> usize {
+    options.threads.filter(|&threads| threads > 0).unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+    })
 }
 
-fn find_dynamic_tail_start(
-    energies: &[f32],
-    frame_ms: usize,
-    speech_ref: f32,
-) -> Option<usize> {
-    let num_frames = energies.len();
-    if num_frames == 0 || speech_ref <= 0.0 {
-        return None;
-    }
-    let window_ms = 200usize;
-    let min_tail_ms = 300usize;
-    let drop_ratio = 0.25f32;
-    let window_frames = ((window_ms + frame_ms - 1) / frame_ms)
-        .max(3)
-        .min(num_frames);
-    let min_tail_frames = (min_tail_ms + frame_ms - 1) / frame_ms;
-    if num_frames < window_frames + min_tail_frames {
-        return None;
-    }
-    let mut prefix = Vec::with_capacity(num_frames + 1);
-    prefix.push(0.0);
-    for &e in energies {
-        let last = *prefix.last().unwrap();
-        prefix.push(last + e);
-    }
-    let threshold = speech_ref * drop_ratio;
-    let mut tail_start: Option<usize> = None;
-    for i in (0..=num_frames - window_frames).rev() {
-        let sum = prefix[i + window_frames] - prefix[i];
-        let avg = sum / window_frames as f32;
-        if avg >= threshold {
-            tail_start = Some(i + window_frames);
-            break;
-        }
-    }
-    let Some(tail_start) = tail_start else {
-        return None;
-    };
-    let tail_frames = num_frames.saturating_sub(tail_start);
-    if tail_frames < min_tail_frames {
-        return None;
-    }
-    Some(tail_start)
+/// Hand out an in-memory mono buffer in resampler-sized chunks.
+fn mono_chunks(mono: &[f32]) -> impl FnMut() -> Result<Option<Vec<f32>>> + '_ {
+    let mut chunks = mono.chunks(RECORDING_CHUNK_FRAMES);
+    move || Ok(chunks.next().map(<[f32]>::to_vec))
 }
 
-fn prefilter_speech(samples: &mut Vec<f32>, sample_rate: u32) -> Option<VadResult> {
-    if samples.is_empty() || sample_rate == 0 {
-        return None;
-    }
-    let original_len = samples.len();
-    let frame_ms = 20usize;
-    let frame_len = (sample_rate as usize * frame_ms) / 1000;
-    if frame_len == 0 || original_len < frame_len * 2 {
-        return None;
-    }
-
-    let num_frames = (samples.len() + frame_len - 1) / frame_len;
-    if num_frames == 0 {
-        return None;
-    }
-
-    let mut energies = Vec::with_capacity(num_frames);
-    for i in 0..num_frames {
-        let start = i * frame_len;
-        let end = std::cmp::min(start + frame_len, samples.len());
-        if start >= end {
-            energies.push(0.0);
-            continue;
-        }
-        let mut sum = 0.0f32;
-        for &sample in &samples[start..end] {
-            sum += sample.abs();
-        }
-        energies.push(sum / (end - start) as f32);
-    }
-
-    let mut sorted = energies.clone();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    let noise_idx = ((sorted.len() as f32 - 1.0) * 0.1).round() as usize;
-    let noise_floor = sorted[noise_idx.min(sorted.len() - 1)];
-    let threshold = (noise_floor * 2.5).max(0.002);
-    let speech_ref = speech_median(&energies, threshold);
-    let dynamic_tail_start =
-        speech_ref.and_then(|speech_ref| find_dynamic_tail_start(&energies, frame_ms, speech_ref));
-    let tail_window_ms = 800usize;
-    let tail_frames = (tail_window_ms + frame_ms - 1) / frame_ms;
-    let tail_start = num_frames.saturating_sub(tail_frames);
-    let tail_threshold = (noise_floor * 2.0).max(0.0015);
-
-    let min_speech_ms = 200usize;
-    let pad_ms = 240usize;
-    let keep_silence_ms = 800usize;
-    let min_speech_frames = (min_speech_ms + frame_ms - 1) / frame_ms;
-    let pad_frames = (pad_ms + frame_ms - 1) / frame_ms;
-    let keep_silence_frames = (keep_silence_ms + frame_ms - 1) / frame_ms;
-
-    let mut raw_segments: Vec<(usize, usize)> = Vec::new();
-    let mut current_start: Option<usize> = None;
-    for (idx, &energy) in energies.iter().enumerate() {
-        let frame_threshold = if idx >= tail_start {
-            tail_threshold
-        } else {
-            threshold
-        };
-        if energy >= frame_threshold {
-            if current_start.is_none() {
-                current_start = Some(idx);
-            }
-        } else if let Some(start) = current_start.take() {
-            let end = idx.saturating_sub(1);
-            if end + 1 - start >= min_speech_frames {
-                raw_segments.push((start, end));
+/// Resample mono chunks from `next_chunk` to 16 kHz and pass them to `on_window` in
+/// windows of at most `window_samples`, cut at quiet points. `on_window` gets each
+/// window's start sample and whether it is the whole stream; returns the total
+/// number of 16 kHz samples.
+fn stream_windows(
+    sample_rate: u32,
+    window_samples: usize,
+    mut next_chunk: impl FnMut() -> Result<Option<Vec<f32>>>,
+    cancel: &CancelToken,
+    mut on_window: impl FnMut(Vec<f32>, usize, bool) -> Result<()>,
+) -> Result<usize> {
+    let mut resampler = StreamingResampler::new(sample_rate)?;
+    let mut window: Vec<f32> = Vec::new();
+    let mut window_start = 0usize;
+    let mut finished = false;
+    while !finished {
+        cancel.check()?;
+        match next_chunk()? {
+            Some(chunk) => window.extend(resampler.process(&chunk)?),
+            None => {
+                window.extend(resampler.flush()?);
+                finished = true;
             }
         }
-    }
-    if let Some(start) = current_start.take() {
-        let end = num_frames.saturating_sub(1);
-        if end + 1 - start >= min_speech_frames {
-            raw_segments.push((start, end));
+        while window.len() >= window_samples || (finished && !window.is_empty()) {
+            let split = if window.len() >= window_samples {
+                quiet_split_point(&window[..window_samples], STREAM_SPLIT_SEARCH_SAMPLES)
+            } else {
+                window.len()
+            };
+            let rest = window.split_off(split);
+            let samples = std::mem::replace(&mut window, rest);
+            let window_len = samples.len();
+            let whole_stream = window_start == 0 && finished && window.is_empty();
+            on_window(samples, window_start, whole_stream)?;
+            window_start += window_len;
         }
     }
+    Ok(window_start)
+}
 
-    if raw_segments.is_empty() {
-        samples.clear();
-        return Some(VadResult {
-            removed_samples: original_len,
-            kept_samples: 0,
-            segments: 0,
-            threshold,
-            noise_floor,
-            keep_silence_ms,
-            pad_ms,
-        });
-    }
-
-    let mut padded: Vec<(usize, usize)> = Vec::with_capacity(raw_segments.len());
-    for (start, end) in raw_segments {
-        let start = start.saturating_sub(pad_frames);
-        let end = (end + pad_frames).min(num_frames.saturating_sub(1));
-        padded.push((start, end));
-    }
-
-    let mut merged: Vec<(usize, usize)> = Vec::new();
-    for (start, end) in padded {
-        if let Some(last) = merged.last_mut() {
-            if start <= last.1 + keep_silence_frames {
-                last.1 = last.1.max(end);
-                continue;
-            }
-        }
-        merged.push((start, end));
-    }
+/// Encoder context just long enough for a clip, instead of the full 30 s.
+fn low_latency_audio_ctx(samples_16k: usize) -> i32 {
+    let frames = samples_16k.div_ceil(16_000 / AUDIO_CTX_PER_SEC) + AUDIO_CTX_MARGIN;
+    frames.clamp(256, AUDIO_CTX_PER_SEC * 30) as i32
+}
 
-    if let Some(tail_start) = dynamic_tail_start {
-        if let Some(last) = merged.last_mut() {
-            let tail_start = tail_start.max(last.1 + 1);
-            if tail_start > last.1 + 1 {
-                last.1 = tail_start.saturating_sub(1).min(num_frames.saturating_sub(1));
-            }
-        }
+/// Pick the latency mode from the clip's full length, then clean up the audio and cut
+/// out silence. The returned map takes times in the kept samples back to the input.
+fn prepare_for_inference(
+    mut samples_16k: Vec<f32>,
+    options: &TranscribeOptions,
+) -> (Vec<f32>, TimeMap, TranscribeOptions) {
+    let mut options = options.clone();
+    options.latency = options.latency.resolve(samples_16k.len());
+    if let Some(stats) = preprocess(&mut samples_16k, 16_000, &options.preprocess) {
+        tracing::debug!(
+            dc_offset = stats.dc_offset,
+            gated_frames = stats.gated_frames,
+            gain_db = stats.gain_db,
+            "preprocessed audio"
+        );
     }
-
-    if merged.len() == 1 && merged[0].0 == 0 && merged[0].1 + 1 >= num_frames {
-        return None;
+    let mut time_map = TimeMap::identity(samples_16k.len());
+    let detector = options.vad.detector();
+    if let Some(vad) = prefilter_speech(
+        &mut samples_16k,
+        16_000,
+        detector.as_ref(),
+        &options.vad,
+        &mut time_map,
+    ) {
+        tracing::debug!(
+            detector = detector.name(),
+            removed_samples = vad.removed_samples,
+            kept_samples = vad.kept_samples,
+            removed_sec = vad.removed_samples as f32 / 16_000.0,
+            segments = vad.segments,
+            threshold = vad.threshold,
+            noise_floor = vad.noise_floor,
+            keep_silence_ms = vad.keep_silence_ms,
+            pad_ms = vad.pad_ms,
+            "prefiltered non-speech"
+        );
     }
-
-    let mut new_samples = Vec::with_capacity(original_len);
-    let insert_silence_ms = 120usize;
-    let insert_silence_len = (sample_rate as usize * insert_silence_ms) / 1000;
-    for (idx, (start_frame, end_frame)) in merged.iter().enumerate() {
-        let start_sample = (start_frame * frame_len).min(samples.len());
-        let end_sample = ((end_frame + 1) * frame_len).min(samples.len());
-        if start_sample < end_sample {
-            new_samples.extend_from_slice(&samples[start_sample..end_sample]);
-            if idx + 1 < merged.len() && insert_silence_len > 0 {
-                new_samples.resize(new_samples.len() + insert_silence_len, 0.0);
-            }
-        }
+    if let Some(trim) = trim_silence(&mut samples_16k, 16_000, detector.as_ref(), &mut time_map)
+    {
+        tracing::debug!(
+            trimmed_samples = trim.trimmed_samples,
+            trimmed_leading_samples = trim.trimmed_leading_samples,
+            trimmed_trailing_samples = trim.trimmed_trailing_samples,
+            trimmed_sec = trim.trimmed_samples as f32 / 16_000.0,
+            threshold = trim.threshold,
+            noise_floor = trim.noise_floor,
+            leading_frames = trim.leading_frames,
+            trailing_frames = trim.trailing_frames,
+            "trimmed leading/trailing silence"
+        );
     }
+    (samples_16k, time_map, options)
+}
 
-    let kept_samples = new_samples.len();
-    let removed_samples = original_len.saturating_sub(kept_samples);
-    if removed_samples == 0 {
-        return None;
+/// Combine the configured prompt with the tail of already transcribed text.
+pub fn continuation_prompt(base: Option<&str>, previous: &str) -> Option<String> {
+    let previous = previous.trim();
+    if previous.is_empty() {
+        return base.map(str::to_string);
     }
-    *samples = new_samples;
-    Some(VadResult {
-        removed_samples,
-        kept_samples,
-        segments: merged.len(),
-        threshold,
-        noise_floor,
-        keep_silence_ms,
-        pad_ms,
+    let tail_start = previous
+        .char_indices()
+        .rev()
+        .nth(CONTINUATION_PROMPT_CHARS)
+        .map(|(idx, _)| idx)
+        .unwrap_or(0);
+    let tail = previous[tail_start..].trim_start();
+    Some(match base {
+        Some(base) => format!("{base} {tail}"),
+        None => tail.to_string(),
     })
 }
 
-struct TrimResult {
-    trimmed_samples: usize,
-    trimmed_leading_samples: usize,
-    trimmed_trailing_samples: usize,
-    threshold: f32,
-    noise_floor: f32,
-    leading_frames: usize,
-    trailing_frames: usize,
+/// Pick a cut position in the last `search` samples of `samples`: the middle of
+/// the quietest 20 ms frame, so window boundaries rarely split a word.
+fn quiet_split_point(samples: &[f32], search: usize) -> usize {
+    let search_start = samples.len().saturating_sub(search);
+    quietest_frame(samples, search_start, samples.len()).max(1)
 }
 
-fn trim_silence(samples: &mut Vec<f32>, sample_rate: u32) -> Option<TrimResult> {
-    if samples.is_empty() || sample_rate == 0 {
-        return None;
+/// Cut points that divide `samples` into `parts` chunks of similar length, each
+/// moved to the quietest frame near its target so no chunk starts mid-word.
+fn silence_split_points(samples: &[f32], parts: usize) -> Vec<usize> {
+    let mut cuts = Vec::new();
+    if parts < 2 {
+        return cuts;
     }
-    let original_len = samples.len();
-    let frame_ms = 20usize;
-    let frame_len = (sample_rate as usize * frame_ms) / 1000;
-    if frame_len == 0 {
-        return None;
-    }
-    let num_frames = (samples.len() + frame_len - 1) / frame_len;
-    if num_frames == 0 {
-        return None;
-    }
-
-    let mut energies = Vec::with_capacity(num_frames);
-    for i in 0..num_frames {
-        let start = i * frame_len;
-        let end = std::cmp::min(start + frame_len, samples.len());
+    let chunk_len = samples.len() / parts;
+    let search = (chunk_len / 4).min(PARALLEL_SPLIT_SEARCH_SAMPLES);
+    let mut previous = 0usize;
+    for idx in 1..parts {
+        let target = chunk_len * idx;
+        let start = target.saturating_sub(search).max(previous + 1);
+        let end = (target + search).min(samples.len());
         if start >= end {
-            energies.push(0.0);
             continue;
         }
-        let mut sum = 0.0f32;
-        for &sample in &samples[start..end] {
-            sum += sample.abs();
-        }
-        energies.push(sum / (end - start) as f32);
-    }
-
-    let mut sorted = energies.clone();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    let noise_idx = ((sorted.len() as f32 - 1.0) * 0.1).round() as usize;
-    let noise_floor = sorted[noise_idx.min(sorted.len() - 1)];
-    let threshold = (noise_floor * 2.5).max(0.002);
-    let speech_ref = speech_median(&energies, threshold);
-    let dynamic_tail_start =
-        speech_ref.and_then(|speech_ref| find_dynamic_tail_start(&energies, frame_ms, speech_ref));
-    let tail_window_ms = 800usize;
-    let tail_frames = (tail_window_ms + frame_ms - 1) / frame_ms;
-    let tail_start = num_frames.saturating_sub(tail_frames);
-    let tail_threshold = (noise_floor * 2.0).max(0.0015);
-
-    let mut first_loud: Option<usize> = None;
-    let mut last_loud: Option<usize> = None;
-    for (idx, &energy) in energies.iter().enumerate() {
-        let frame_threshold = if idx >= tail_start {
-            tail_threshold
-        } else {
-            threshold
-        };
-        if energy >= frame_threshold {
-            if first_loud.is_none() {
-                first_loud = Some(idx);
-            }
-            last_loud = Some(idx);
+        let cut = quietest_frame(samples, start, end);
+        if cut > previous && cut < samples.len() {
+            cuts.push(cut);
+            previous = cut;
         }
     }
+    cuts
+}
 
-    let Some(first_loud) = first_loud else {
-        samples.clear();
-        return Some(TrimResult {
-            trimmed_samples: original_len,
-            trimmed_leading_samples: original_len,
-            trimmed_trailing_samples: 0,
-            threshold,
-            noise_floor,
-            leading_frames: num_frames,
-            trailing_frames: 0,
-        });
-    };
-    let last_loud = last_loud.unwrap_or(first_loud);
-
-    let min_leading_silence_ms = 300usize;
-    let min_trailing_silence_ms = 400usize;
-    let pad_before_ms = 200usize;
-    let pad_after_ms = 240usize;
-
-    let min_leading_frames = (min_leading_silence_ms + frame_ms - 1) / frame_ms;
-    let min_trailing_frames = (min_trailing_silence_ms + frame_ms - 1) / frame_ms;
-    let pad_before_frames = (pad_before_ms + frame_ms - 1) / frame_ms;
-    let pad_after_frames = (pad_after_ms + frame_ms - 1) / frame_ms;
-
-    let leading_frames = first_loud;
-    let mut trailing_frames = num_frames.saturating_sub(last_loud + 1);
-
-    let start_frame = if leading_frames >= min_leading_frames {
-        first_loud.saturating_sub(pad_before_frames)
-    } else {
-        0
-    };
-    let mut end_frame = if trailing_frames >= min_trailing_frames {
-        (last_loud + 1 + pad_after_frames).min(num_frames)
-    } else {
-        num_frames
-    };
-    if let Some(tail_start) = dynamic_tail_start {
-        let tail_start = tail_start.max(last_loud + 1);
-        let tail_frames = num_frames.saturating_sub(tail_start);
-        if tail_frames >= min_trailing_frames {
-            trailing_frames = tail_frames;
-            end_frame = (tail_start + pad_after_frames).min(num_frames);
-        }
-    }
-
-    if start_frame == 0 && end_frame == num_frames {
-        return None;
-    }
-
-    let start_sample = (start_frame * frame_len).min(samples.len());
-    let end_sample = (end_frame * frame_len).min(samples.len());
-    if start_sample >= end_sample {
-        samples.clear();
-        return Some(TrimResult {
-            trimmed_samples: original_len,
-            trimmed_leading_samples: original_len,
-            trimmed_trailing_samples: 0,
-            threshold,
-            noise_floor,
-            leading_frames,
-            trailing_frames,
-        });
-    }
-
-    let trimmed_leading_samples = start_sample;
-    let trimmed_trailing_samples = original_len - end_sample;
-    let keep_len = end_sample - start_sample;
-    samples.copy_within(start_sample..end_sample, 0);
-    samples.truncate(keep_len);
-    let trimmed_samples = trimmed_leading_samples + trimmed_trailing_samples;
-    Some(TrimResult {
-        trimmed_samples,
-        trimmed_leading_samples,
-        trimmed_trailing_samples,
-        threshold,
-        noise_floor,
-        leading_frames,
-        trailing_frames,
-    })
+/// Middle of the lowest-energy 20 ms frame in `samples[start..end]`.
+fn quietest_frame(samples: &[f32], start: usize, end: usize) -> usize {
+    let frame_len = 320usize;
+    frame_energies(&samples[start..end], frame_len)
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| (idx + 1) * frame_len <= end - start)
+        .min_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(Ordering::Equal))
+        .map(|(idx, _)| start + idx * frame_len + frame_len / 2)
+        .unwrap_or(end)
 }
 
 #[cfg(test)]
@@ -923,6 +1429,171 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn group_words_joins_pieces_and_averages_probability() {
+        let piece = |bytes: &[u8], start_ms: u64, end_ms: u64, probability: f32| TokenPiece {
+            bytes: bytes.to_vec(),
+            start_ms,
+            end_ms,
+            probability,
+        };
+        // " Привет" split in the middle of a multi-byte character.
+        let privet = " Привет".as_bytes();
+        let words = group_words(&[
+            piece(b" Hello", 0, 400, 0.9),
+            piece(b",", 400, 450, 0.7),
+            piece(b" un", 500, 600, 1.0),
+            piece(b"believ", 600, 800, 0.4),
+            piece(b"able", 800, 900, 0.4),
+            piece(&privet[..4], 1_000, 1_100, 0.5),
+            piece(&privet[4..], 1_100, 1_300, 0.5),
+        ]);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["Hello,", "unbelievable", "Привет"]);
+        assert_eq!((words[1].start_ms, words[1].end_ms), (500, 900));
+        assert!((words[0].probability - 0.8).abs() < 1e-6);
+        assert!((words[1].probability - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn translating_from_english_detects_the_source_language() {
+        let options = |language: Option<&str>, translate| TranscribeOptions {
+            language: language.map(str::to_string),
+            translate,
+            ..TranscribeOptions::default()
+        };
+        assert!(options(Some("auto"), false).detects_language());
+        assert!(options(Some("en"), true).detects_language());
+        assert!(options(None, true).detects_language());
+        assert!(!options(Some("en"), false).detects_language());
+        assert!(!options(Some("ru"), true).detects_language());
+    }
+
+    #[test]
+    fn pick_language_respects_allow_list() {
+        let codes = ["en", "de", "ru"];
+        let lang_str = |id: i32| codes.get(id as usize).copied();
+        let probs = [0.2, 0.7, 0.1];
+        let any = pick_language(&probs, &[], lang_str).unwrap();
+        assert_eq!(any.code, "de");
+        let allowed = vec!["EN".to_string(), "ru".to_string()];
+        let limited = pick_language(&probs, &allowed, lang_str).unwrap();
+        assert_eq!(limited.code, "en");
+        assert_eq!(limited.probability, 0.2);
+        assert!(pick_language(&probs, &["fr".to_string()], lang_str).is_none());
+    }
+
+    #[test]
+    fn short_clips_take_the_fast_path_with_a_fitted_context() {
+        assert_eq!(LatencyMode::Auto.resolve(16_000 * 5), LatencyMode::Fast);
+        assert_eq!(LatencyMode::Auto.resolve(16_000 * 15), LatencyMode::Accurate);
+        assert_eq!(LatencyMode::Accurate.resolve(16_000), LatencyMode::Accurate);
+        assert_eq!(LatencyMode::Fast.resolve(16_000 * 120), LatencyMode::Fast);
+        assert_eq!(low_latency_audio_ctx(16_000), 256);
+        assert_eq!(low_latency_audio_ctx(16_000 * 10), 628);
+        assert_eq!(low_latency_audio_ctx(16_000 * 60), 1_500);
+    }
+
+    #[test]
+    fn latency_follows_clip_length_not_the_speech_left_after_vad() {
+        // A minute of silence around three seconds of tone: VAD keeps well under 15 s.
+        let mut samples = vec![0.0f32; 16_000 * 60];
+        for (i, sample) in samples[16_000 * 30..16_000 * 33].iter_mut().enumerate() {
+            *sample = 0.3 * (i as f32 * 300.0 * std::f32::consts::TAU / 16_000.0).sin();
+        }
+        let (kept, _, options) = prepare_for_inference(samples, &TranscribeOptions::default());
+        assert!(kept.len() < LOW_LATENCY_MAX_SAMPLES, "kept {} samples", kept.len());
+        assert_eq!(options.latency, LatencyMode::Accurate);
+
+        let short = vec![0.0f32; 16_000 * 5];
+        let (_, _, options) = prepare_for_inference(short, &TranscribeOptions::default());
+        assert_eq!(options.latency, LatencyMode::Fast);
+    }
+
+    #[test]
+    fn stereo_48k_recordings_stream_as_one_16k_mono_window() -> Result<()> {
+        let sample_rate = 48_000u32;
+        let frames = sample_rate as usize * 3;
+        // A 440 Hz tone on the left channel only, silence on the right.
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                [0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin(), 0.0]
+            })
+            .collect();
+        let mono = downmix_to_mono(&samples, 2);
+        assert_eq!(mono.len(), frames);
+        let mut windows = Vec::new();
+        let total = stream_windows(
+            sample_rate,
+            STREAM_WINDOW_SAMPLES,
+            mono_chunks(&mono),
+            &CancelToken::default(),
+            |samples, start, whole_stream| {
+                windows.push((samples, start, whole_stream));
+                Ok(())
+            },
+        )?;
+        assert_eq!(windows.len(), 1);
+        let (samples, start, whole_stream) = &windows[0];
+        assert_eq!((*start, *whole_stream), (0, true));
+        assert_eq!(total, samples.len());
+        assert!(total.abs_diff(48_000) <= 64, "{total} samples at 16 kHz");
+        // The downmixed tone keeps half its amplitude: RMS 0.25 / sqrt(2).
+        let middle = &samples[8_000..40_000];
+        let rms = (middle.iter().map(|s| s * s).sum::<f32>() / middle.len() as f32).sqrt();
+        assert!((rms - 0.177).abs() < 0.01, "rms {rms}");
+        Ok(())
+    }
+
+    #[test]
+    fn stream_windows_grow_to_feed_every_core() {
+        assert_eq!(stream_window_samples(4), STREAM_WINDOW_SAMPLES);
+        for cores in [32, 64, 128] {
+            // After VAD cuts up to half the window, there is still a minimum-length
+            // chunk for every group of threads.
+            let kept = stream_window_samples(cores) / PARALLEL_WINDOW_HEADROOM;
+            assert_eq!(
+                kept / PARALLEL_MIN_CHUNK_SAMPLES,
+                cores / PARALLEL_MIN_THREADS_PER_CHUNK,
+                "{cores} cores"
+            );
+        }
+    }
+
+    #[test]
+    fn silence_split_points_prefer_quiet_frames() {
+        let mut samples = vec![0.3f32; 16_000 * 120];
+        let quiet = 16_000 * 55;
+        samples[quiet..quiet + 3_200].fill(0.0);
+        let cuts = silence_split_points(&samples, 2);
+        assert_eq!(cuts.len(), 1);
+        assert!((quiet..quiet + 3_200).contains(&cuts[0]));
+        assert!(silence_split_points(&samples, 1).is_empty());
+    }
+
+    #[test]
+    fn quiet_split_point_cuts_in_silence_near_window_end() {
+        let mut samples = vec![0.3f32; 16_000 * 10];
+        let quiet = 16_000 * 8;
+        samples[quiet..quiet + 1_600].fill(0.0);
+        let split = quiet_split_point(&samples, 16_000 * 4);
+        assert!((quiet..quiet + 1_600).contains(&split));
+    }
+
+    #[test]
+    fn continuation_prompt_appends_the_tail_of_earlier_text() {
+        assert_eq!(
+            continuation_prompt(Some("Vocabulary: Dictate"), " Said before."),
+            Some("Vocabulary: Dictate Said before.".to_string())
+        );
+        assert_eq!(continuation_prompt(Some("Dictate"), "  "), Some("Dictate".to_string()));
+        let long = "word ".repeat(100);
+        let prompt = continuation_prompt(None, &long).unwrap_or_default();
+        assert!(prompt.len() < long.trim().len() / 2, "{prompt:?}");
+        assert!(prompt.ends_with("word"), "{prompt:?}");
+    }
+
     fn write_silence_wav(path: &Path) -> Result<()> {
         let spec = hound::WavSpec {
             channels: 1,
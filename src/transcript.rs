@@ -0,0 +1,255 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Transcript {
+    /// Language whisper detected, when it was asked to detect one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<DetectedLanguage>,
+    /// Where local inference ran; `None` for remote backends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<ComputeDevice>,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputeDevice {
+    Cpu,
+    Gpu,
+}
+
+impl ComputeDevice {
+    pub fn label(self) -> &'static str {
+        match self {
+            ComputeDevice::Cpu => "cpu",
+            ComputeDevice::Gpu => "gpu",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// Whisper language code, e.g. "en" or "ru".
+    pub code: String,
+    /// Probability the model assigned to this language (0.0..=1.0).
+    pub probability: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Mean probability of the text tokens in this segment (0.0..=1.0).
+    pub avg_token_prob: f32,
+    /// Probability that the segment contains no speech: reported by remote backends,
+    /// estimated from voice activity for local inference.
+    pub no_speech_prob: Option<f32>,
+    /// Who is talking, for transcripts built from one channel per speaker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    pub words: Vec<Word>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Word {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Mean probability of the tokens that make up the word (0.0..=1.0).
+    pub probability: f32,
+}
+
+impl Transcript {
+    pub fn new(segments: Vec<Segment>) -> Self {
+        Self {
+            language: None,
+            device: None,
+            segments,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Plain text of the whole transcript, as whisper emits it (segments concatenated, trimmed).
+    /// Speaker-labelled transcripts get one `Speaker N: ...` line per turn instead.
+    pub fn text(&self) -> String {
+        if self.has_speakers() {
+            return self
+                .turns()
+                .into_iter()
+                .map(|turn| format!("{}: {}", turn.speaker, turn.text()))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        let mut out = String::new();
+        for segment in &self.segments {
+            out.push_str(&segment.text);
+        }
+        out.trim().to_string()
+    }
+
+    pub fn has_speakers(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| segment.speaker.is_some())
+    }
+
+    /// Runs of consecutive non-empty segments by the same speaker.
+    pub fn turns(&self) -> Vec<Turn<'_>> {
+        let mut turns: Vec<Turn<'_>> = Vec::new();
+        for segment in &self.segments {
+            if segment.text.trim().is_empty() {
+                continue;
+            }
+            let speaker = segment.speaker.as_deref().unwrap_or("Speaker");
+            match turns.last_mut() {
+                Some(turn) if turn.speaker == speaker => turn.segments.push(segment),
+                _ => turns.push(Turn {
+                    speaker,
+                    segments: vec![segment],
+                }),
+            }
+        }
+        turns
+    }
+
+    /// Interleave per-channel transcripts in time order, labelling channel `i` as
+    /// `Speaker i+1`. Segment times must share the same origin.
+    pub fn merge_speakers(channels: Vec<Transcript>) -> Transcript {
+        let mut merged = Transcript::default();
+        for (idx, channel) in channels.into_iter().enumerate() {
+            if merged.language.is_none() {
+                merged.language = channel.language;
+            }
+            if merged.device.is_none() {
+                merged.device = channel.device;
+            }
+            let speaker = format!("Speaker {}", idx + 1);
+            merged
+                .segments
+                .extend(channel.segments.into_iter().map(|mut segment| {
+                    segment.speaker = Some(speaker.clone());
+                    segment
+                }));
+        }
+        merged.segments.sort_by_key(|segment| segment.start_ms);
+        merged
+    }
+
+    /// Append segments from a transcript of audio that started `offset_ms` into this one.
+    pub fn extend_shifted(&mut self, other: Transcript, offset_ms: u64) {
+        if self.language.is_none() {
+            self.language = other.language;
+        }
+        if self.device.is_none() {
+            self.device = other.device;
+        }
+        for mut segment in other.segments {
+            segment.start_ms += offset_ms;
+            segment.end_ms += offset_ms;
+            for word in &mut segment.words {
+                word.start_ms += offset_ms;
+                word.end_ms += offset_ms;
+            }
+            self.segments.push(segment);
+        }
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.segments.iter().map(|s| s.end_ms).max().unwrap_or(0)
+    }
+}
+
+pub struct Turn<'a> {
+    pub speaker: &'a str,
+    pub segments: Vec<&'a Segment>,
+}
+
+impl Turn<'_> {
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            out.push_str(&segment.text);
+        }
+        out.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, end_ms: u64, text: &str) -> Segment {
+        Segment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            avg_token_prob: 0.9,
+            no_speech_prob: None,
+            speaker: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn text_concatenates_and_trims_segments() {
+        let transcript = Transcript::new(vec![
+            segment(0, 1_200, " Hello there."),
+            segment(1_200, 2_500, " How are you? "),
+        ]);
+        assert_eq!(transcript.text(), "Hello there. How are you?");
+        assert_eq!(transcript.duration_ms(), 2_500);
+    }
+
+    #[test]
+    fn extend_shifted_offsets_segments_and_words() {
+        let mut transcript = Transcript::new(vec![segment(0, 1_000, " One.")]);
+        let mut later = segment(500, 1_500, " Two.");
+        later.words.push(Word {
+            start_ms: 600,
+            end_ms: 900,
+            text: "Two.".to_string(),
+            probability: 0.8,
+        });
+        transcript.extend_shifted(Transcript::new(vec![later]), 60_000);
+        assert_eq!(transcript.segments[1].start_ms, 60_500);
+        assert_eq!(transcript.segments[1].end_ms, 61_500);
+        assert_eq!(transcript.segments[1].words[0].start_ms, 60_600);
+        assert_eq!(transcript.text(), "One. Two.");
+    }
+
+    #[test]
+    fn merge_speakers_interleaves_channels_by_time() {
+        let caller = Transcript::new(vec![
+            segment(0, 2_000, " Hi, it's Anna."),
+            segment(2_000, 3_000, " Got a minute?"),
+            segment(6_000, 7_000, " Great."),
+        ]);
+        let callee = Transcript::new(vec![segment(3_500, 5_500, " Sure, go ahead.")]);
+        let merged = Transcript::merge_speakers(vec![caller, callee]);
+        let speakers: Vec<_> = merged
+            .segments
+            .iter()
+            .map(|segment| segment.speaker.as_deref().unwrap_or_default())
+            .collect();
+        assert_eq!(
+            speakers,
+            ["Speaker 1", "Speaker 1", "Speaker 2", "Speaker 1"]
+        );
+        assert_eq!(
+            merged.text(),
+            "Speaker 1: Hi, it's Anna. Got a minute?\nSpeaker 2: Sure, go ahead.\nSpeaker 1: Great."
+        );
+    }
+
+    #[test]
+    fn empty_transcript_has_no_text() {
+        let transcript = Transcript::default();
+        assert!(transcript.is_empty());
+        assert_eq!(transcript.text(), "");
+        assert_eq!(transcript.duration_ms(), 0);
+    }
+}
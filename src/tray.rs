@@ -1,10 +1,14 @@
 use crate::audio::AudioDevice;
+use crate::config::HotkeyMode;
+use crate::hotkeys::{HotkeyAction, HotkeyBinding, label_for};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use tray_icon::menu::{CheckMenuItem, Menu, MenuId, MenuItem, PredefinedMenuItem};
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
 const ICON_SIZE: usize = 44;
+/// Characters of live transcript kept in the tooltip; the tail is the interesting part.
+const LIVE_PREVIEW_CHARS: usize = 120;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Theme {
@@ -25,6 +29,8 @@ pub enum TrayAction {
     Quit,
     SelectMic(Option<String>),
     ToggleRecording,
+    CancelTranscription,
+    RecoverRecordings,
 }
 
 pub struct TrayController {
@@ -32,12 +38,19 @@ pub struct TrayController {
     menu: Menu,
     status_item: MenuItem,
     start_stop_item: MenuItem,
+    cancel_item: MenuItem,
+    /// Shown only while interrupted recordings are waiting to be recovered.
+    recover_item: MenuItem,
+    recover_shown: bool,
     default_mic_item: CheckMenuItem,
     mic_items: HashMap<MenuId, (String, CheckMenuItem)>,
     mic_separator: PredefinedMenuItem,
     quit_id: MenuId,
     icons: TrayIcons,
     idle_theme: Theme,
+    hotkey_mode: HotkeyMode,
+    /// Label of the recording hotkey, if one is bound.
+    record_keys: Option<String>,
 }
 
 struct TrayIcons {
@@ -52,13 +65,21 @@ impl TrayController {
         devices: &[AudioDevice],
         current_mic: Option<&str>,
         default_mic_label: Option<&str>,
+        hotkey_mode: HotkeyMode,
+        hotkeys: &[HotkeyBinding],
     ) -> Result<Self> {
+        let record_keys = label_for(hotkeys, &HotkeyAction::ToggleRecording);
+        let cancel_label = match label_for(hotkeys, &HotkeyAction::Cancel) {
+            Some(keys) => format!("Cancel Transcription ({keys})"),
+            None => "Cancel Transcription".to_string(),
+        };
         let menu_parts = Self::build_menu(
             devices,
             current_mic,
             default_mic_label,
             "Status: Idle",
-            "Start Recording (Option+Space)",
+            &start_stop_label(hotkey_mode, record_keys.as_deref(), false),
+            &cancel_label,
         )?;
 
         let icons = TrayIcons::new()?;
@@ -76,12 +97,17 @@ impl TrayController {
             menu: menu_parts.menu,
             status_item: menu_parts.status_item,
             start_stop_item: menu_parts.start_stop_item,
+            cancel_item: menu_parts.cancel_item,
+            recover_item: MenuItem::new("Recover Unfinished Recordings", true, None),
+            recover_shown: false,
             default_mic_item: menu_parts.default_mic_item,
             mic_items: menu_parts.mic_items,
             mic_separator: menu_parts.mic_separator,
             quit_id: menu_parts.quit_id,
             icons,
             idle_theme,
+            hotkey_mode,
+            record_keys,
         })
     }
 
@@ -124,6 +150,12 @@ impl TrayController {
         if id == self.start_stop_item.id().clone() {
             return Some(TrayAction::ToggleRecording);
         }
+        if id == self.cancel_item.id().clone() {
+            return Some(TrayAction::CancelTranscription);
+        }
+        if id == self.recover_item.id().clone() {
+            return Some(TrayAction::RecoverRecordings);
+        }
         if id == self.quit_id {
             return Some(TrayAction::Quit);
         }
@@ -135,6 +167,34 @@ impl TrayController {
             .map(|(name, _)| TrayAction::SelectMic(Some(name.clone())))
     }
 
+    /// Offer to recover `count` interrupted recordings; 0 hides the item.
+    pub fn set_recoverable(&mut self, count: usize) -> Result<()> {
+        if count == 0 {
+            if self.recover_shown {
+                self.menu.remove(&self.recover_item)?;
+                self.recover_shown = false;
+            }
+            return Ok(());
+        }
+        let label = if count == 1 {
+            "Recover Unfinished Recording".to_string()
+        } else {
+            format!("Recover {count} Unfinished Recordings")
+        };
+        self.recover_item.set_text(label);
+        if !self.recover_shown {
+            let position = self
+                .menu
+                .items()
+                .iter()
+                .position(|item| item.id() == self.cancel_item.id())
+                .map_or(0, |idx| idx + 1);
+            self.menu.insert(&self.recover_item, position)?;
+            self.recover_shown = true;
+        }
+        Ok(())
+    }
+
     pub fn set_selected_mic(&self, name: Option<&str>) {
         self.default_mic_item.set_checked(name.is_none());
         for (_id, (mic_name, item)) in &self.mic_items {
@@ -151,18 +211,18 @@ impl TrayController {
     }
 
     pub fn set_state(&self, state: TrayState) -> Result<()> {
+        self.cancel_item
+            .set_enabled(matches!(state, TrayState::Transcribing { .. }));
         match state {
             TrayState::Idle => {
                 self.apply_icon(self.icons.idle_for_theme(self.idle_theme), true)?;
                 self.status_item.set_text("Status: Idle");
-                self.start_stop_item
-                    .set_text("Start Recording (Option+Space)");
+                self.start_stop_item.set_text(self.start_stop_label(false));
             }
             TrayState::Recording => {
                 self.apply_icon(self.icons.recording.clone(), false)?;
                 self.status_item.set_text("Status: Recording");
-                self.start_stop_item
-                    .set_text("Stop Recording (Option+Space)");
+                self.start_stop_item.set_text(self.start_stop_label(true));
             }
             TrayState::Transcribing { progress } => {
                 let icon = icon_transcribing(progress)?;
@@ -172,8 +232,7 @@ impl TrayController {
                     None => "Status: Transcribing".to_string(),
                 };
                 self.status_item.set_text(&label);
-                self.start_stop_item
-                    .set_text("Start Recording (Option+Space)");
+                self.start_stop_item.set_text(self.start_stop_label(false));
             }
             TrayState::Downloading { progress } => {
                 self.apply_icon(self.icons.downloading.clone(), false)?;
@@ -182,13 +241,36 @@ impl TrayController {
                     None => "Status: Loading model".to_string(),
                 };
                 self.status_item.set_text(&label);
-                self.start_stop_item
-                    .set_text("Start Recording (Option+Space)");
+                self.start_stop_item.set_text(self.start_stop_label(false));
             }
         }
         Ok(())
     }
 
+    /// Show the latest live transcript in the tooltip; `None` restores the default.
+    pub fn set_live_preview(&self, text: Option<&str>) -> Result<()> {
+        let tooltip = match text {
+            Some(text) if !text.is_empty() => {
+                let tail_start = text
+                    .char_indices()
+                    .rev()
+                    .nth(LIVE_PREVIEW_CHARS)
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(0);
+                if tail_start == 0 {
+                    text.to_string()
+                } else {
+                    format!("…{}", &text[tail_start..])
+                }
+            }
+            _ => "Dictate".to_string(),
+        };
+        self.tray
+            .set_tooltip(Some(tooltip))
+            .context("set tray tooltip")?;
+        Ok(())
+    }
+
     pub fn sync_idle_theme(&mut self) -> Result<()> {
         let theme = current_theme();
         if theme != self.idle_theme {
@@ -198,6 +280,10 @@ impl TrayController {
         Ok(())
     }
 
+    fn start_stop_label(&self, recording: bool) -> String {
+        start_stop_label(self.hotkey_mode, self.record_keys.as_deref(), recording)
+    }
+
     fn apply_icon(&self, icon: Icon, is_template: bool) -> Result<()> {
         self.tray.set_icon(Some(icon))?;
         self.tray.set_icon_as_template(is_template);
@@ -227,6 +313,7 @@ struct MenuParts {
     menu: Menu,
     status_item: MenuItem,
     start_stop_item: MenuItem,
+    cancel_item: MenuItem,
     default_mic_item: CheckMenuItem,
     mic_items: HashMap<MenuId, (String, CheckMenuItem)>,
     mic_separator: PredefinedMenuItem,
@@ -240,15 +327,18 @@ impl TrayController {
         default_mic_label: Option<&str>,
         status_label: &str,
         start_stop_label: &str,
+        cancel_label: &str,
     ) -> Result<MenuParts> {
         let status_item = MenuItem::new(status_label, false, None);
         let start_stop_item = MenuItem::new(start_stop_label, true, None);
+        let cancel_item = MenuItem::new(cancel_label, false, None);
         let quit_item = PredefinedMenuItem::quit(None);
         let quit_id = quit_item.id().clone();
 
         let menu = Menu::new();
         menu.append(&status_item)?;
         menu.append(&start_stop_item)?;
+        menu.append(&cancel_item)?;
         menu.append(&PredefinedMenuItem::separator())?;
         let mic_header = MenuItem::new("Microphones", false, None);
         menu.append(&mic_header)?;
@@ -274,6 +364,7 @@ impl TrayController {
             menu,
             status_item,
             start_stop_item,
+            cancel_item,
             default_mic_item,
             mic_items,
             mic_separator,
@@ -282,6 +373,18 @@ impl TrayController {
     }
 }
 
+/// The menu item still toggles recording on click; the label explains the hotkey.
+fn start_stop_label(mode: HotkeyMode, keys: Option<&str>, recording: bool) -> String {
+    match (mode, keys, recording) {
+        (HotkeyMode::Toggle, Some(keys), false) => format!("Start Recording ({keys})"),
+        (HotkeyMode::Toggle, Some(keys), true) => format!("Stop Recording ({keys})"),
+        (HotkeyMode::PushToTalk, Some(keys), false) => format!("Hold {keys} to Record"),
+        (HotkeyMode::PushToTalk, Some(keys), true) => format!("Release {keys} to Stop"),
+        (_, None, false) => "Start Recording".to_string(),
+        (_, None, true) => "Stop Recording".to_string(),
+    }
+}
+
 struct IdlePalette {
     body: [u8; 4],
     arm: [u8; 4],
@@ -634,3 +737,19 @@ fn draw_arc_aa(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_stop_label_follows_the_hotkey_mode() {
+        let label = |mode, recording| start_stop_label(mode, Some("Option+Space"), recording);
+        assert_eq!(label(HotkeyMode::Toggle, false), "Start Recording (Option+Space)");
+        assert_eq!(label(HotkeyMode::Toggle, true), "Stop Recording (Option+Space)");
+        assert_eq!(label(HotkeyMode::PushToTalk, false), "Hold Option+Space to Record");
+        assert_eq!(label(HotkeyMode::PushToTalk, true), "Release Option+Space to Stop");
+        assert_eq!(start_stop_label(HotkeyMode::PushToTalk, None, false), "Start Recording");
+        assert_eq!(start_stop_label(HotkeyMode::Toggle, None, true), "Stop Recording");
+    }
+}
@@ -0,0 +1,815 @@
+use crate::transcript::Transcript;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Decides which frames of a 16 kHz buffer contain speech. `prefilter_speech` and
+/// `trim_silence` turn those decisions into cuts.
+pub trait SpeechDetector: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Classify fixed-length frames of `samples`; `None` when the buffer is too
+    /// short to analyse.
+    fn detect(&self, samples: &[f32], sample_rate: u32) -> Option<FrameDecisions>;
+}
+
+#[derive(Debug, Clone)]
+pub struct FrameDecisions {
+    pub frame_ms: usize,
+    pub frame_len: usize,
+    pub speech: Vec<bool>,
+    /// First frame after the last stretch of audio near speech level; audio past
+    /// it is a fading tail rather than speech.
+    pub tail_start: Option<usize>,
+    pub threshold: f32,
+    pub noise_floor: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectorKind {
+    /// Mean amplitude against the noise floor; fine for quiet rooms.
+    #[default]
+    Energy,
+    /// Energy in the 300–3400 Hz speech band; ignores engine rumble and hum.
+    Spectral,
+}
+
+/// Voice activity detection settings, configurable globally, per watch and per run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    pub detector: DetectorKind,
+    /// Analysis frame length.
+    pub frame_ms: usize,
+    /// Frames louder than this multiple of the noise floor count as speech.
+    pub threshold_ratio: f32,
+    /// Lowest speech threshold, as mean absolute amplitude.
+    pub min_threshold: f32,
+    /// Shorter runs of speech frames are treated as clicks and dropped.
+    pub min_speech_ms: usize,
+    /// Audio kept on each side of a speech run.
+    pub pad_ms: usize,
+    /// Pauses shorter than this between speech runs are kept as-is.
+    pub keep_silence_ms: usize,
+    /// Spectral detector only: share of frame energy that must fall in the speech band.
+    pub min_band_ratio: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            detector: DetectorKind::Energy,
+            frame_ms: 20,
+            threshold_ratio: 2.5,
+            min_threshold: 0.002,
+            min_speech_ms: 200,
+            pad_ms: 240,
+            keep_silence_ms: 800,
+            min_band_ratio: 0.35,
+        }
+    }
+}
+
+impl VadConfig {
+    pub fn detector(&self) -> Box<dyn SpeechDetector> {
+        match self.detector {
+            DetectorKind::Energy => Box::new(EnergyDetector {
+                frame_ms: self.frame_ms,
+                threshold_ratio: self.threshold_ratio,
+                min_threshold: self.min_threshold,
+            }),
+            DetectorKind::Spectral => Box::new(SpectralDetector {
+                frame_ms: self.frame_ms,
+                threshold_ratio: self.threshold_ratio,
+                min_threshold: self.min_threshold,
+                min_band_ratio: self.min_band_ratio,
+            }),
+        }
+    }
+}
+
+/// Speech is anything louder than a multiple of the quietest 10% of frames.
+pub struct EnergyDetector {
+    pub frame_ms: usize,
+    pub threshold_ratio: f32,
+    pub min_threshold: f32,
+}
+
+impl SpeechDetector for EnergyDetector {
+    fn name(&self) -> &'static str {
+        "energy"
+    }
+
+    fn detect(&self, samples: &[f32], sample_rate: u32) -> Option<FrameDecisions> {
+        let frame_len = frame_len(sample_rate, self.frame_ms)?;
+        let energies = frame_energies(samples, frame_len);
+        classify_energies(
+            &energies,
+            self.frame_ms,
+            frame_len,
+            self.threshold_ratio,
+            self.min_threshold,
+            |_| true,
+        )
+    }
+}
+
+/// Energy detector run on the 300–3400 Hz band, which also requires a frame to
+/// carry most of its energy in that band. Car and HVAC noise sits below it and
+/// no longer masks quieter speech.
+pub struct SpectralDetector {
+    pub frame_ms: usize,
+    pub threshold_ratio: f32,
+    pub min_threshold: f32,
+    pub min_band_ratio: f32,
+}
+
+impl SpeechDetector for SpectralDetector {
+    fn name(&self) -> &'static str {
+        "spectral"
+    }
+
+    fn detect(&self, samples: &[f32], sample_rate: u32) -> Option<FrameDecisions> {
+        let frame_len = frame_len(sample_rate, self.frame_ms)?;
+        let band = speech_band(samples, sample_rate);
+        let band_energies = frame_energies(&band, frame_len);
+        let energies = frame_energies(samples, frame_len);
+        classify_energies(
+            &band_energies,
+            self.frame_ms,
+            frame_len,
+            self.threshold_ratio,
+            self.min_threshold,
+            |idx| {
+                let total = energies[idx];
+                total <= 0.0 || band_energies[idx] / total >= self.min_band_ratio
+            },
+        )
+    }
+}
+
+fn frame_len(sample_rate: u32, frame_ms: usize) -> Option<usize> {
+    let frame_len = (sample_rate as usize * frame_ms) / 1000;
+    (frame_len > 0 && frame_ms > 0).then_some(frame_len)
+}
+
+/// Threshold frame energies against the noise floor. The last 800 ms use a lower
+/// threshold so trailing words that fade out are kept.
+fn classify_energies(
+    energies: &[f32],
+    frame_ms: usize,
+    frame_len: usize,
+    threshold_ratio: f32,
+    min_threshold: f32,
+    gate: impl Fn(usize) -> bool,
+) -> Option<FrameDecisions> {
+    let num_frames = energies.len();
+    if num_frames == 0 {
+        return None;
+    }
+    let mut sorted = energies.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let noise_idx = ((sorted.len() as f32 - 1.0) * 0.1).round() as usize;
+    let noise_floor = sorted[noise_idx.min(sorted.len() - 1)];
+    let threshold = (noise_floor * threshold_ratio).max(min_threshold);
+    let speech_ref = speech_median(energies, threshold);
+    let tail_start =
+        speech_ref.and_then(|speech_ref| find_dynamic_tail_start(energies, frame_ms, speech_ref));
+    let tail_window_ms = 800usize;
+    let tail_frames = tail_window_ms.div_ceil(frame_ms);
+    let tail_from = num_frames.saturating_sub(tail_frames);
+    let tail_threshold = (noise_floor * threshold_ratio * 0.8).max(min_threshold * 0.75);
+
+    let speech = energies
+        .iter()
+        .enumerate()
+        .map(|(idx, &energy)| {
+            let frame_threshold = if idx >= tail_from {
+                tail_threshold
+            } else {
+                threshold
+            };
+            energy >= frame_threshold && gate(idx)
+        })
+        .collect();
+    Some(FrameDecisions {
+        frame_ms,
+        frame_len,
+        speech,
+        tail_start,
+        threshold,
+        noise_floor,
+    })
+}
+
+/// Band-pass the signal to roughly 300–3400 Hz (RBJ biquad, constant peak gain).
+fn speech_band(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    let (low, high) = (300.0f32, 3400.0f32.min(sample_rate as f32 * 0.45));
+    let center = (low * high).sqrt();
+    let q = center / (high - low);
+    let w0 = std::f32::consts::TAU * center / sample_rate as f32;
+    let alpha = w0.sin() / (2.0 * q);
+    let a0 = 1.0 + alpha;
+    let (b0, b2) = (alpha / a0, -alpha / a0);
+    let a1 = -2.0 * w0.cos() / a0;
+    let a2 = (1.0 - alpha) / a0;
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    samples
+        .iter()
+        .map(|&x| {
+            let y = b0 * x + b2 * x2 - a1 * y1 - a2 * y2;
+            x2 = x1;
+            x1 = x;
+            y2 = y1;
+            y1 = y;
+            y
+        })
+        .collect()
+}
+
+/// Maps sample positions in a buffer that had silence cut out (and short gaps
+/// inserted) back to positions in the original decoded audio.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TimeMap {
+    spans: Vec<MappedSpan>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MappedSpan {
+    output_start: usize,
+    source_start: usize,
+    len: usize,
+}
+
+impl TimeMap {
+    pub(crate) fn identity(len: usize) -> Self {
+        Self {
+            spans: vec![MappedSpan {
+                output_start: 0,
+                source_start: 0,
+                len,
+            }],
+        }
+    }
+
+    /// Record that the buffer was replaced by the concatenation of `kept` ranges
+    /// (in current buffer positions), with `gap` inserted samples between ranges.
+    fn retain(&mut self, kept: &[(usize, usize)], gap: usize) {
+        let mut spans = Vec::new();
+        let mut output_pos = 0usize;
+        for (idx, &(start, end)) in kept.iter().enumerate() {
+            for span in &self.spans {
+                let overlap_start = span.output_start.max(start);
+                let overlap_end = (span.output_start + span.len).min(end);
+                if overlap_start < overlap_end {
+                    spans.push(MappedSpan {
+                        output_start: output_pos + (overlap_start - start),
+                        source_start: span.source_start + (overlap_start - span.output_start),
+                        len: overlap_end - overlap_start,
+                    });
+                }
+            }
+            output_pos += end - start;
+            if idx + 1 < kept.len() {
+                output_pos += gap;
+            }
+        }
+        self.spans = spans;
+    }
+
+    /// Source position for a segment start; positions inside an inserted gap snap
+    /// forward to the next kept span.
+    fn source_start(&self, pos: usize) -> usize {
+        for span in &self.spans {
+            if pos < span.output_start + span.len {
+                return span.source_start + pos.saturating_sub(span.output_start);
+            }
+        }
+        self.spans
+            .last()
+            .map(|span| span.source_start + span.len)
+            .unwrap_or(pos)
+    }
+
+    /// Source position for a segment end; positions inside an inserted gap snap
+    /// back to the end of the previous kept span.
+    fn source_end(&self, pos: usize) -> usize {
+        let mut mapped = self
+            .spans
+            .first()
+            .map(|span| span.source_start)
+            .unwrap_or(pos);
+        for span in &self.spans {
+            if pos < span.output_start {
+                break;
+            }
+            mapped = span.source_start + (pos - span.output_start).min(span.len);
+        }
+        mapped
+    }
+
+    pub(crate) fn remap_transcript(&self, transcript: &mut Transcript, sample_rate: u32) {
+        let rate = sample_rate as u64;
+        let to_pos = |ms: u64| (ms * rate / 1000) as usize;
+        let to_ms = |pos: usize| pos as u64 * 1000 / rate;
+        for segment in &mut transcript.segments {
+            let start_ms = to_ms(self.source_start(to_pos(segment.start_ms)));
+            let end_ms = to_ms(self.source_end(to_pos(segment.end_ms)));
+            segment.start_ms = start_ms;
+            segment.end_ms = end_ms.max(start_ms);
+            for word in &mut segment.words {
+                let start_ms = to_ms(self.source_start(to_pos(word.start_ms)));
+                let end_ms = to_ms(self.source_end(to_pos(word.end_ms)));
+                word.start_ms = start_ms;
+                word.end_ms = end_ms.max(start_ms);
+            }
+        }
+    }
+}
+
+pub(crate) struct VadResult {
+    pub(crate) removed_samples: usize,
+    pub(crate) kept_samples: usize,
+    pub(crate) segments: usize,
+    pub(crate) threshold: f32,
+    pub(crate) noise_floor: f32,
+    pub(crate) keep_silence_ms: usize,
+    pub(crate) pad_ms: usize,
+}
+
+/// Mean absolute amplitude of each `frame_len`-sample frame (the last may be short).
+pub(crate) fn frame_energies(samples: &[f32], frame_len: usize) -> Vec<f32> {
+    samples
+        .chunks(frame_len.max(1))
+        .map(|frame| frame.iter().map(|s| s.abs()).sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+fn speech_median(energies: &[f32], threshold: f32) -> Option<f32> {
+    let mut speech: Vec<f32> = energies
+        .iter()
+        .copied()
+        .filter(|&e| e >= threshold)
+        .collect();
+    if speech.len() < 3 {
+        return None;
+    }
+    speech.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Some(speech[speech.len() / 2])
+}
+
+fn find_dynamic_tail_start(energies: &[f32], frame_ms: usize, speech_ref: f32) -> Option<usize> {
+    let num_frames = energies.len();
+    if num_frames == 0 || speech_ref <= 0.0 {
+        return None;
+    }
+    let window_ms = 200usize;
+    let min_tail_ms = 300usize;
+    let drop_ratio = 0.25f32;
+    let window_frames = window_ms.div_ceil(frame_ms).max(3).min(num_frames);
+    let min_tail_frames = min_tail_ms.div_ceil(frame_ms);
+    if num_frames < window_frames + min_tail_frames {
+        return None;
+    }
+    let mut prefix = Vec::with_capacity(num_frames + 1);
+    prefix.push(0.0);
+    for &e in energies {
+        let last = *prefix.last().unwrap();
+        prefix.push(last + e);
+    }
+    let threshold = speech_ref * drop_ratio;
+    let mut tail_start: Option<usize> = None;
+    for i in (0..=num_frames - window_frames).rev() {
+        let sum = prefix[i + window_frames] - prefix[i];
+        let avg = sum / window_frames as f32;
+        if avg >= threshold {
+            tail_start = Some(i + window_frames);
+            break;
+        }
+    }
+    let tail_start = tail_start?;
+    let tail_frames = num_frames.saturating_sub(tail_start);
+    if tail_frames < min_tail_frames {
+        return None;
+    }
+    Some(tail_start)
+}
+
+/// Cut non-speech stretches out of `samples`, joining the remaining speech runs
+/// with short silent gaps.
+pub(crate) fn prefilter_speech(
+    samples: &mut Vec<f32>,
+    sample_rate: u32,
+    detector: &dyn SpeechDetector,
+    config: &VadConfig,
+    time_map: &mut TimeMap,
+) -> Option<VadResult> {
+    if samples.is_empty() || sample_rate == 0 {
+        return None;
+    }
+    let original_len = samples.len();
+    let decisions = detector.detect(samples, sample_rate)?;
+    let frame_ms = decisions.frame_ms;
+    let frame_len = decisions.frame_len;
+    if original_len < frame_len * 2 {
+        return None;
+    }
+    let num_frames = decisions.speech.len();
+    let threshold = decisions.threshold;
+    let noise_floor = decisions.noise_floor;
+
+    let min_speech_ms = config.min_speech_ms;
+    let pad_ms = config.pad_ms;
+    let keep_silence_ms = config.keep_silence_ms;
+    let min_speech_frames = min_speech_ms.div_ceil(frame_ms);
+    let pad_frames = pad_ms.div_ceil(frame_ms);
+    let keep_silence_frames = keep_silence_ms.div_ceil(frame_ms);
+
+    let mut raw_segments: Vec<(usize, usize)> = Vec::new();
+    let mut current_start: Option<usize> = None;
+    for (idx, &speech) in decisions.speech.iter().enumerate() {
+        if speech {
+            if current_start.is_none() {
+                current_start = Some(idx);
+            }
+        } else if let Some(start) = current_start.take() {
+            let end = idx.saturating_sub(1);
+            if end + 1 - start >= min_speech_frames {
+                raw_segments.push((start, end));
+            }
+        }
+    }
+    if let Some(start) = current_start.take() {
+        let end = num_frames.saturating_sub(1);
+        if end + 1 - start >= min_speech_frames {
+            raw_segments.push((start, end));
+        }
+    }
+
+    if raw_segments.is_empty() {
+        samples.clear();
+        time_map.retain(&[], 0);
+        return Some(VadResult {
+            removed_samples: original_len,
+            kept_samples: 0,
+            segments: 0,
+            threshold,
+            noise_floor,
+            keep_silence_ms,
+            pad_ms,
+        });
+    }
+
+    let mut padded: Vec<(usize, usize)> = Vec::with_capacity(raw_segments.len());
+    for (start, end) in raw_segments {
+        let start = start.saturating_sub(pad_frames);
+        let end = (end + pad_frames).min(num_frames.saturating_sub(1));
+        padded.push((start, end));
+    }
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in padded {
+        if let Some(last) = merged.last_mut() {
+            if start <= last.1 + keep_silence_frames {
+                last.1 = last.1.max(end);
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+
+    if let Some(tail_start) = decisions.tail_start {
+        if let Some(last) = merged.last_mut() {
+            let tail_start = tail_start.max(last.1 + 1);
+            if tail_start > last.1 + 1 {
+                last.1 = tail_start
+                    .saturating_sub(1)
+                    .min(num_frames.saturating_sub(1));
+            }
+        }
+    }
+
+    if merged.len() == 1 && merged[0].0 == 0 && merged[0].1 + 1 >= num_frames {
+        return None;
+    }
+
+    let mut new_samples = Vec::with_capacity(original_len);
+    let insert_silence_ms = 120usize;
+    let insert_silence_len = (sample_rate as usize * insert_silence_ms) / 1000;
+    let kept: Vec<(usize, usize)> = merged
+        .iter()
+        .map(|(start_frame, end_frame)| {
+            let start_sample = (start_frame * frame_len).min(samples.len());
+            let end_sample = ((end_frame + 1) * frame_len).min(samples.len());
+            (start_sample, end_sample)
+        })
+        .filter(|(start, end)| start < end)
+        .collect();
+    for (idx, &(start_sample, end_sample)) in kept.iter().enumerate() {
+        new_samples.extend_from_slice(&samples[start_sample..end_sample]);
+        if idx + 1 < kept.len() && insert_silence_len > 0 {
+            new_samples.resize(new_samples.len() + insert_silence_len, 0.0);
+        }
+    }
+
+    let kept_samples = new_samples.len();
+    let removed_samples = original_len.saturating_sub(kept_samples);
+    if removed_samples == 0 {
+        return None;
+    }
+    *samples = new_samples;
+    time_map.retain(&kept, insert_silence_len);
+    Some(VadResult {
+        removed_samples,
+        kept_samples,
+        segments: merged.len(),
+        threshold,
+        noise_floor,
+        keep_silence_ms,
+        pad_ms,
+    })
+}
+
+pub(crate) struct TrimResult {
+    pub(crate) trimmed_samples: usize,
+    pub(crate) trimmed_leading_samples: usize,
+    pub(crate) trimmed_trailing_samples: usize,
+    pub(crate) threshold: f32,
+    pub(crate) noise_floor: f32,
+    pub(crate) leading_frames: usize,
+    pub(crate) trailing_frames: usize,
+}
+
+/// Drop leading and trailing silence around the first and last speech frames.
+pub(crate) fn trim_silence(
+    samples: &mut Vec<f32>,
+    sample_rate: u32,
+    detector: &dyn SpeechDetector,
+    time_map: &mut TimeMap,
+) -> Option<TrimResult> {
+    if samples.is_empty() || sample_rate == 0 {
+        return None;
+    }
+    let original_len = samples.len();
+    let decisions = detector.detect(samples, sample_rate)?;
+    let frame_ms = decisions.frame_ms;
+    let frame_len = decisions.frame_len;
+    let num_frames = decisions.speech.len();
+    let threshold = decisions.threshold;
+    let noise_floor = decisions.noise_floor;
+
+    let first_loud = decisions.speech.iter().position(|&speech| speech);
+    let last_loud = decisions.speech.iter().rposition(|&speech| speech);
+
+    let Some(first_loud) = first_loud else {
+        samples.clear();
+        time_map.retain(&[], 0);
+        return Some(TrimResult {
+            trimmed_samples: original_len,
+            trimmed_leading_samples: original_len,
+            trimmed_trailing_samples: 0,
+            threshold,
+            noise_floor,
+            leading_frames: num_frames,
+            trailing_frames: 0,
+        });
+    };
+    let last_loud = last_loud.unwrap_or(first_loud);
+
+    let min_leading_silence_ms = 300usize;
+    let min_trailing_silence_ms = 400usize;
+    let pad_before_ms = 200usize;
+    let pad_after_ms = 240usize;
+
+    let min_leading_frames = min_leading_silence_ms.div_ceil(frame_ms);
+    let min_trailing_frames = min_trailing_silence_ms.div_ceil(frame_ms);
+    let pad_before_frames = pad_before_ms.div_ceil(frame_ms);
+    let pad_after_frames = pad_after_ms.div_ceil(frame_ms);
+
+    let leading_frames = first_loud;
+    let mut trailing_frames = num_frames.saturating_sub(last_loud + 1);
+
+    let start_frame = if leading_frames >= min_leading_frames {
+        first_loud.saturating_sub(pad_before_frames)
+    } else {
+        0
+    };
+    let mut end_frame = if trailing_frames >= min_trailing_frames {
+        (last_loud + 1 + pad_after_frames).min(num_frames)
+    } else {
+        num_frames
+    };
+    if let Some(tail_start) = decisions.tail_start {
+        let tail_start = tail_start.max(last_loud + 1);
+        let tail_frames = num_frames.saturating_sub(tail_start);
+        if tail_frames >= min_trailing_frames {
+            trailing_frames = tail_frames;
+            end_frame = (tail_start + pad_after_frames).min(num_frames);
+        }
+    }
+
+    if start_frame == 0 && end_frame == num_frames {
+        return None;
+    }
+
+    let start_sample = (start_frame * frame_len).min(samples.len());
+    let end_sample = (end_frame * frame_len).min(samples.len());
+    if start_sample >= end_sample {
+        samples.clear();
+        time_map.retain(&[], 0);
+        return Some(TrimResult {
+            trimmed_samples: original_len,
+            trimmed_leading_samples: original_len,
+            trimmed_trailing_samples: 0,
+            threshold,
+            noise_floor,
+            leading_frames,
+            trailing_frames,
+        });
+    }
+
+    let trimmed_leading_samples = start_sample;
+    let trimmed_trailing_samples = original_len - end_sample;
+    let keep_len = end_sample - start_sample;
+    samples.copy_within(start_sample..end_sample, 0);
+    samples.truncate(keep_len);
+    time_map.retain(&[(start_sample, end_sample)], 0);
+    let trimmed_samples = trimmed_leading_samples + trimmed_trailing_samples;
+    Some(TrimResult {
+        trimmed_samples,
+        trimmed_leading_samples,
+        trimmed_trailing_samples,
+        threshold,
+        noise_floor,
+        leading_frames,
+        trailing_frames,
+    })
+}
+
+/// Fill in `no_speech_prob` for segments whose backend reported none, as the share
+/// of frames in the segment's span the detector heard no speech in. Segment times
+/// must be relative to `samples`.
+pub(crate) fn estimate_no_speech(
+    transcript: &mut Transcript,
+    samples: &[f32],
+    sample_rate: u32,
+    detector: &dyn SpeechDetector,
+) {
+    if transcript.segments.iter().all(|segment| segment.no_speech_prob.is_some()) {
+        return;
+    }
+    let Some(decisions) = detector.detect(samples, sample_rate) else {
+        return;
+    };
+    let frame_ms = decisions.frame_ms as u64;
+    for segment in &mut transcript.segments {
+        if segment.no_speech_prob.is_some() {
+            continue;
+        }
+        let first = (segment.start_ms / frame_ms) as usize;
+        let last = (segment.end_ms.div_ceil(frame_ms) as usize).min(decisions.speech.len());
+        let Some(frames) = decisions.speech.get(first..last).filter(|frames| !frames.is_empty())
+        else {
+            continue;
+        };
+        let silent = frames.iter().filter(|&&speech| !speech).count();
+        segment.no_speech_prob = Some(silent as f32 / frames.len() as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Segment;
+
+    fn tone(samples: &mut [f32], sample_rate: u32, from_sec: f32, to_sec: f32, hz: f32, amp: f32) {
+        for (idx, sample) in samples.iter_mut().enumerate() {
+            let t = idx as f32 / sample_rate as f32;
+            if (from_sec..to_sec).contains(&t) {
+                *sample += amp * (t * hz * std::f32::consts::TAU).sin();
+            }
+        }
+    }
+
+    #[test]
+    fn time_map_composes_prefilter_and_trim() {
+        let mut map = TimeMap::identity(1_000);
+        // Keep [100, 300) and [600, 800) with a 50-sample gap: output length 450.
+        map.retain(&[(100, 300), (600, 800)], 50);
+        assert_eq!(map.source_start(0), 100);
+        assert_eq!(map.source_start(199), 299);
+        assert_eq!(map.source_start(220), 600);
+        assert_eq!(map.source_end(220), 300);
+        assert_eq!(map.source_end(260), 610);
+        // Then trim 20 leading samples from the filtered buffer.
+        map.retain(&[(20, 450)], 0);
+        assert_eq!(map.source_start(0), 120);
+        assert_eq!(map.source_start(240), 610);
+    }
+
+    #[test]
+    fn no_speech_is_estimated_from_frames_in_each_segment() {
+        let sample_rate = 16_000u32;
+        let mut samples = vec![0.0f32; sample_rate as usize * 4];
+        tone(&mut samples, sample_rate, 0.0, 2.0, 440.0, 0.3);
+        let segment = |start_ms, end_ms, no_speech_prob| Segment {
+            start_ms,
+            end_ms,
+            text: " words".to_string(),
+            avg_token_prob: 0.2,
+            no_speech_prob,
+            speaker: None,
+            words: Vec::new(),
+        };
+        let mut transcript = Transcript::new(vec![
+            segment(0, 2_000, None),
+            segment(2_000, 4_000, None),
+            segment(2_000, 4_000, Some(0.1)),
+        ]);
+        let detector = VadConfig::default().detector();
+        estimate_no_speech(&mut transcript, &samples, sample_rate, detector.as_ref());
+        let estimates: Vec<f32> = transcript
+            .segments
+            .iter()
+            .map(|segment| segment.no_speech_prob.unwrap_or(-1.0))
+            .collect();
+        assert!(estimates[0] < 0.1, "{estimates:?}");
+        assert!(estimates[1] > 0.9, "{estimates:?}");
+        assert_eq!(estimates[2], 0.1);
+    }
+
+    #[test]
+    fn prefilter_offsets_map_back_to_source_time() {
+        let sample_rate = 16_000u32;
+        let rate = sample_rate as usize;
+        let mut samples = vec![0.0f32; rate * 8];
+        // Tone bursts at 2.0-3.0 s and 6.0-7.0 s.
+        tone(&mut samples, sample_rate, 2.0, 3.0, 440.0, 0.3);
+        tone(&mut samples, sample_rate, 6.0, 7.0, 440.0, 0.3);
+        let config = VadConfig::default();
+        let detector = config.detector();
+        let mut map = TimeMap::identity(samples.len());
+        assert!(prefilter_speech(
+            &mut samples,
+            sample_rate,
+            detector.as_ref(),
+            &config,
+            &mut map
+        )
+        .is_some());
+        trim_silence(&mut samples, sample_rate, detector.as_ref(), &mut map);
+        assert!(samples.len() < rate * 4);
+
+        let mut transcript = Transcript::new(vec![Segment {
+            start_ms: 0,
+            end_ms: (samples.len() as u64 * 1000) / sample_rate as u64,
+            text: " one two".to_string(),
+            avg_token_prob: 1.0,
+            no_speech_prob: None,
+            speaker: None,
+            words: Vec::new(),
+        }]);
+        map.remap_transcript(&mut transcript, sample_rate);
+        let segment = &transcript.segments[0];
+        assert!(
+            (1_500..=2_000).contains(&segment.start_ms),
+            "start {}",
+            segment.start_ms
+        );
+        assert!(
+            (7_000..=7_500).contains(&segment.end_ms),
+            "end {}",
+            segment.end_ms
+        );
+    }
+
+    #[test]
+    fn spectral_detector_hears_speech_over_engine_rumble() {
+        let sample_rate = 16_000u32;
+        let mut samples = vec![0.0f32; sample_rate as usize * 8];
+        tone(&mut samples, sample_rate, 0.0, 8.0, 60.0, 0.2);
+        tone(&mut samples, sample_rate, 3.0, 5.0, 1_000.0, 0.1);
+        let speech_frames = |kind: DetectorKind| {
+            let config = VadConfig {
+                detector: kind,
+                ..VadConfig::default()
+            };
+            let decisions = config.detector().detect(&samples, sample_rate).unwrap();
+            let frame_sec = decisions.frame_ms as f32 / 1000.0;
+            decisions
+                .speech
+                .iter()
+                .enumerate()
+                .filter(|(_, speech)| **speech)
+                .map(|(idx, _)| idx as f32 * frame_sec)
+                .collect::<Vec<f32>>()
+        };
+        let energy = speech_frames(DetectorKind::Energy);
+        // The rumble lifts the noise floor above the speech for the energy detector.
+        assert!(energy.is_empty(), "energy frames {}", energy.len());
+        let spectral = speech_frames(DetectorKind::Spectral);
+        assert!(spectral.len() > 80, "spectral frames {}", spectral.len());
+        assert!(spectral.iter().all(|t| (2.9..5.1).contains(t)));
+    }
+}
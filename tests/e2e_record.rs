@@ -1,7 +1,9 @@
 use anyhow::{anyhow, Result};
-use dictate::audio::{encode_m4a, CpalRecorder};
+use dictate::audio::CpalRecorder;
+use dictate::encode::{encode_m4a, encode_wav};
+use dictate::journal;
 use dictate::model;
-use dictate::transcriber::WhisperTranscriber;
+use dictate::transcriber::{TranscribeOptions, WhisperTranscriber};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
@@ -18,7 +20,8 @@ fn e2e_record_and_transcribe() -> Result<()> {
         return Ok(());
     }
     let dir = tempdir()?;
-    let handle = match CpalRecorder::start_recording(None) {
+    let journal = dir.path().join("recording.journal");
+    let handle = match CpalRecorder::start_recording(None, Some(&journal)) {
         Ok(handle) => handle,
         Err(err) => {
             eprintln!("skipping e2e_record_and_transcribe: {err}");
@@ -27,6 +30,7 @@ fn e2e_record_and_transcribe() -> Result<()> {
     };
     thread::sleep(Duration::from_secs(2));
     let recorded = handle.stop()?;
+    assert_eq!(journal::read_journal(&journal)?.samples, recorded.samples);
 
     let audio = dir.path().join("recording.m4a");
     encode_m4a(&recorded, &audio)?;
@@ -46,9 +50,31 @@ fn e2e_record_and_transcribe() -> Result<()> {
     let transcriber = WhisperTranscriber::new(model_path)?;
     let text = transcriber.transcribe_file(&audio)?;
     fs::write(dir.path().join("recording.md"), &text)?;
+
+    // AAC damage changes what whisper hears; compare against a lossless copy.
+    let wav = dir.path().join("recording.wav");
+    encode_wav(&recorded, &wav)?;
+    let reference = transcriber.transcribe_file(&wav)?;
+    let in_memory =
+        transcriber.transcribe_recorded(&recorded, None::<fn(i32)>, &TranscribeOptions::default())?;
+    assert_eq!(
+        normalize(&in_memory.text()),
+        normalize(&reference),
+        "in-memory transcript differs from the WAV file's"
+    );
     Ok(())
 }
 
+/// Lowercased words without punctuation, so 16-bit rounding in the WAV copy cannot
+/// fail the comparison over casing or a comma.
+fn normalize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
 #[test]
 fn e2e_transcribe_fixture_audio() -> Result<()> {
     let audio_path = Path::new("tests/fixtures/1234.m4a");